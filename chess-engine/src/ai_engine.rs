@@ -0,0 +1,434 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    chess_move_state::MoveState,
+    chess_piece::{ChessPiece, PieceType},
+    common_chess::{CellPosition, ChessColor, FIRST_ELEMENT, LAST_ELEMENT},
+};
+
+const MATE_SCORE: i32 = 100_000;
+const INFINITY: i32 = 1_000_000;
+const MAX_QUIESCENCE_DEPTH: u32 = 4;
+// non-pawn material of both sides below which kings come out to the center
+const ENDGAME_MATERIAL: i32 = 1_300;
+
+// a move picked by the engine
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EngineMove {
+    pub from: CellPosition,
+    pub to: CellPosition,
+    pub promotion: Option<PieceType>,
+}
+
+pub struct AiEngine;
+
+impl AiEngine {
+    // best move of the side to move found by iterative deepening up to max_depth plies,
+    // the search stops after think_time or once is_cancelled is set
+    // and keeps the result of the last finished depth
+    pub fn best_move(
+        pieces: &[ChessPiece],
+        move_state: &MoveState,
+        max_depth: u32,
+        think_time: Duration,
+        is_cancelled: &AtomicBool,
+    ) -> Option<EngineMove> {
+        let mut pieces = pieces.to_vec();
+        let mut move_state = move_state.clone();
+        let mut search = Search {
+            deadline: Instant::now() + think_time,
+            is_cancelled,
+            is_aborted: false,
+        };
+
+        let mut root_moves = AiEngine::ordered_moves(&pieces, &move_state, false);
+        let mut best_move = root_moves.first()?.clone();
+        for depth in 1..=max_depth {
+            let mut alpha = -INFINITY;
+            let mut depth_best_move = None;
+            for engine_move in root_moves.iter() {
+                let Some(score) = search.search_move(
+                    &mut pieces,
+                    &mut move_state,
+                    engine_move,
+                    |search, pieces, move_state| {
+                        -search.negamax(pieces, move_state, depth - 1, 1, -INFINITY, -alpha)
+                    },
+                ) else {
+                    continue;
+                };
+                if search.is_aborted {
+                    break;
+                }
+                if score > alpha {
+                    alpha = score;
+                    depth_best_move = Some(engine_move.clone());
+                }
+            }
+            if search.is_aborted {
+                break;
+            }
+            if let Some(depth_best_move) = depth_best_move {
+                // the best move is searched first on the next depth
+                root_moves.retain(|engine_move| *engine_move != depth_best_move);
+                root_moves.insert(0, depth_best_move.clone());
+                best_move = depth_best_move;
+            }
+            if alpha >= MATE_SCORE - max_depth as i32 {
+                break;
+            }
+        }
+        Some(best_move)
+    }
+
+    // score of the position for the given side: material and placement of the pieces
+    pub fn evaluate(pieces: &[ChessPiece], color: &ChessColor) -> i32 {
+        let non_pawn_material: i32 = pieces
+            .iter()
+            .filter(|piece| piece.piece_type != PieceType::PAWN)
+            .map(|piece| AiEngine::piece_value(&piece.piece_type))
+            .sum();
+        let is_endgame = non_pawn_material <= ENDGAME_MATERIAL;
+
+        pieces
+            .iter()
+            .map(|piece| {
+                let score = AiEngine::piece_value(&piece.piece_type)
+                    + AiEngine::placement_bonus(piece, is_endgame);
+                if piece.color == *color {
+                    score
+                } else {
+                    -score
+                }
+            })
+            .sum()
+    }
+
+    pub fn piece_value(piece_type: &PieceType) -> i32 {
+        match piece_type {
+            PieceType::PAWN => 100,
+            PieceType::KNIGHT => 320,
+            PieceType::BISHOP => 330,
+            PieceType::ROOK => 500,
+            PieceType::QUEEN => 900,
+            PieceType::KING => 0,
+        }
+    }
+
+    fn placement_bonus(piece: &ChessPiece, is_endgame: bool) -> i32 {
+        let board_size = (LAST_ELEMENT - FIRST_ELEMENT) as i32;
+        let (i, j) = (
+            (piece.pos.i - FIRST_ELEMENT) as i32,
+            (piece.pos.j - FIRST_ELEMENT) as i32,
+        );
+        // 0 on the corners up to 2 * board_size - 2 in the center
+        let centralization =
+            2 * board_size - (2 * i - board_size).abs() - (2 * j - board_size).abs();
+        let advance = match piece.color {
+            ChessColor::WHITE => j,
+            ChessColor::BLACK => board_size - j,
+        };
+        let is_center_file = (2 * i - board_size).abs() <= 1;
+
+        match piece.piece_type {
+            PieceType::PAWN if is_center_file => advance * 12,
+            PieceType::PAWN => advance * 6,
+            PieceType::KNIGHT => centralization * 4,
+            PieceType::BISHOP => centralization * 2,
+            PieceType::ROOK if advance == board_size - 1 => 20,
+            PieceType::ROOK => 0,
+            PieceType::QUEEN => centralization,
+            PieceType::KING if is_endgame => centralization * 4,
+            PieceType::KING => -centralization * 3,
+        }
+    }
+
+    // legal moves of the side to move, a promotion gives one move per piece type
+    pub fn legal_moves(pieces: &[ChessPiece], move_state: &MoveState) -> Vec<EngineMove> {
+        let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+        let mut moves = vec![];
+        for piece in piece_refs
+            .iter()
+            .filter(|piece| piece.color == move_state.current_collor)
+        {
+            for to in piece.get_legal_cells_for_move(&piece_refs, move_state) {
+                if piece.is_promotion_move(&to) {
+                    for piece_type in [
+                        PieceType::QUEEN,
+                        PieceType::KNIGHT,
+                        PieceType::ROOK,
+                        PieceType::BISHOP,
+                    ] {
+                        moves.push(EngineMove {
+                            from: piece.pos,
+                            to,
+                            promotion: Some(piece_type),
+                        });
+                    }
+                } else {
+                    moves.push(EngineMove {
+                        from: piece.pos,
+                        to,
+                        promotion: None,
+                    });
+                }
+            }
+        }
+        moves
+    }
+
+    // legal moves with captures and promotions first
+    fn ordered_moves(
+        pieces: &[ChessPiece],
+        move_state: &MoveState,
+        is_only_captures: bool,
+    ) -> Vec<EngineMove> {
+        let mut scored_moves: Vec<(i32, EngineMove)> = vec![];
+        for engine_move in AiEngine::legal_moves(pieces, move_state) {
+            let Some(piece) = pieces.iter().find(|cp| cp.pos == engine_move.from) else {
+                continue;
+            };
+            let victim = pieces
+                .iter()
+                .find(|cp| cp.pos == engine_move.to)
+                .map(|cp| cp.piece_type.clone())
+                .or_else(|| {
+                    // en passant
+                    (piece.piece_type == PieceType::PAWN && piece.pos.i != engine_move.to.i)
+                        .then_some(PieceType::PAWN)
+                });
+            let is_queen_promotion = engine_move.promotion == Some(PieceType::QUEEN);
+            if is_only_captures && victim.is_none() && !is_queen_promotion {
+                continue;
+            }
+            // most valuable victim, least valuable attacker
+            let capture_score = victim.as_ref().map_or(0, |victim| {
+                10_000 + 10 * AiEngine::piece_value(victim)
+                    - AiEngine::piece_value(&piece.piece_type)
+            });
+            let promotion_score = engine_move
+                .promotion
+                .as_ref()
+                .map_or(0, AiEngine::piece_value);
+            scored_moves.push((capture_score + promotion_score, engine_move));
+        }
+        scored_moves.sort_by_key(|(score, _)| -score);
+        scored_moves
+            .into_iter()
+            .map(|(_, engine_move)| engine_move)
+            .collect()
+    }
+}
+
+struct Search<'a> {
+    deadline: Instant,
+    is_cancelled: &'a AtomicBool,
+    is_aborted: bool,
+}
+
+impl<'a> Search<'a> {
+    // plays the move, scores the position after it and takes the move back
+    fn search_move(
+        &mut self,
+        pieces: &mut Vec<ChessPiece>,
+        move_state: &mut MoveState,
+        engine_move: &EngineMove,
+        score: impl FnOnce(&mut Search<'a>, &mut Vec<ChessPiece>, &mut MoveState) -> i32,
+    ) -> Option<i32> {
+        move_state.make_move(
+            pieces,
+            &engine_move.from,
+            &engine_move.to,
+            engine_move.promotion.clone(),
+        )?;
+        move_state.next_move();
+        let score = score(self, pieces, move_state);
+        move_state.undo_move(pieces);
+        Some(score)
+    }
+
+    // score of a finished game for the side to move, None while it goes on
+    fn game_over_score(move_state: &MoveState, ply: u32) -> Option<i32> {
+        if move_state.mate_state.is_some() {
+            Some(-(MATE_SCORE - ply as i32))
+        } else if move_state.is_game_over() {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    fn is_time_over(&mut self) -> bool {
+        if !self.is_aborted
+            && (Instant::now() >= self.deadline || self.is_cancelled.load(Ordering::Relaxed))
+        {
+            self.is_aborted = true;
+        }
+        self.is_aborted
+    }
+
+    fn negamax(
+        &mut self,
+        pieces: &mut Vec<ChessPiece>,
+        move_state: &mut MoveState,
+        depth: u32,
+        ply: u32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if self.is_time_over() {
+            return 0;
+        }
+        if let Some(score) = Search::game_over_score(move_state, ply) {
+            return score;
+        }
+        if depth == 0 {
+            return self.quiescence(pieces, move_state, MAX_QUIESCENCE_DEPTH, ply, alpha, beta);
+        }
+
+        let mut best_score = -INFINITY;
+        for engine_move in AiEngine::ordered_moves(pieces, move_state, false) {
+            let Some(score) = self.search_move(
+                pieces,
+                move_state,
+                &engine_move,
+                |search, pieces, move_state| {
+                    -search.negamax(pieces, move_state, depth - 1, ply + 1, -beta, -alpha)
+                },
+            ) else {
+                continue;
+            };
+            if self.is_aborted {
+                return 0;
+            }
+            best_score = best_score.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best_score
+    }
+
+    // searches captures only, so the position is not scored in the middle of an exchange
+    fn quiescence(
+        &mut self,
+        pieces: &mut Vec<ChessPiece>,
+        move_state: &mut MoveState,
+        depth: u32,
+        ply: u32,
+        mut alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        if self.is_time_over() {
+            return 0;
+        }
+        if let Some(score) = Search::game_over_score(move_state, ply) {
+            return score;
+        }
+
+        let stand_pat = AiEngine::evaluate(pieces, &move_state.current_collor);
+        if stand_pat >= beta || depth == 0 {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        for engine_move in AiEngine::ordered_moves(pieces, move_state, true) {
+            let Some(score) = self.search_move(
+                pieces,
+                move_state,
+                &engine_move,
+                |search, pieces, move_state| {
+                    -search.quiescence(pieces, move_state, depth - 1, ply + 1, -beta, -alpha)
+                },
+            ) else {
+                continue;
+            };
+            if self.is_aborted {
+                return 0;
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        alpha
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use super::*;
+    use crate::{
+        fen_parser::{FenParser, START_POSITION_FEN},
+        notation::Notation,
+    };
+
+    fn best_move(fen: &str, max_depth: u32) -> Option<EngineMove> {
+        let (pieces, move_state) = FenParser::parse_fen(fen).unwrap();
+        AiEngine::best_move(
+            &pieces,
+            &move_state,
+            max_depth,
+            Duration::from_secs(60),
+            &AtomicBool::new(false),
+        )
+    }
+
+    fn engine_move(from: &str, to: &str, promotion: Option<PieceType>) -> EngineMove {
+        EngineMove {
+            from: Notation::cell_from_string(from).unwrap(),
+            to: Notation::cell_from_string(to).unwrap(),
+            promotion,
+        }
+    }
+
+    #[test]
+    fn test_ai_engine_mate_in_one() {
+        let best_move = best_move("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", 2);
+        assert_eq!(Some(engine_move("a1", "a8", None)), best_move);
+    }
+
+    #[test]
+    fn test_ai_engine_takes_hanging_queen() {
+        let best_move = best_move("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", 2);
+        assert_eq!(Some(engine_move("d2", "d5", None)), best_move);
+    }
+
+    #[test]
+    fn test_ai_engine_promotes_to_queen() {
+        let best_move = best_move("7k/P7/8/8/8/8/1r6/7K w - - 0 1", 2);
+        assert_eq!(
+            Some(engine_move("a7", "a8", Some(PieceType::QUEEN))),
+            best_move
+        );
+    }
+
+    #[test]
+    fn test_ai_engine_no_moves_after_mate() {
+        let best_move = best_move("R5k1/5ppp/8/8/8/8/8/6K1 b - - 1 1", 2);
+        assert_eq!(None, best_move);
+    }
+
+    #[test]
+    fn test_ai_engine_cancelled_search_keeps_a_move() {
+        let (pieces, move_state) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        let best_move = AiEngine::best_move(
+            &pieces,
+            &move_state,
+            8,
+            Duration::from_secs(60),
+            &AtomicBool::new(true),
+        );
+        assert!(best_move.is_some());
+    }
+
+    #[test]
+    fn test_ai_engine_evaluate_start_position() {
+        let (pieces, _) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        assert_eq!(0, AiEngine::evaluate(&pieces, &ChessColor::WHITE));
+    }
+}
@@ -0,0 +1,8 @@
+use std::io;
+
+use chess_engine::uci::Uci;
+
+// the engine of the game for UCI GUIs and tournament managers
+fn main() {
+    Uci::new(io::stdout()).run(io::stdin().lock());
+}
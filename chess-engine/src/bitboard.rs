@@ -0,0 +1,342 @@
+use std::sync::OnceLock;
+
+use crate::{
+    chess_piece::{ChessPiece, PieceType},
+    common_chess::{CellPosition, ChessColor, FIRST_ELEMENT},
+};
+
+// one bit per cell, bit j * 8 + i is set for the cell (i, j)
+pub type Bitboard = u64;
+
+const BOARD_SIZE: i8 = 8;
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (2, 1),
+    (2, -1),
+    (-2, 1),
+    (-2, -1),
+    (1, 2),
+    (1, -2),
+    (-1, 2),
+    (-1, -2),
+];
+const KING_STEPS: [(i8, i8); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (-1, 1),
+    (1, -1),
+    (-1, -1),
+];
+// rook directions first, then bishop ones
+const RAY_DIRECTIONS: [(i8, i8); 8] = KING_STEPS;
+
+// attacks on an empty board for every cell, computed once
+struct AttackTables {
+    knight: [Bitboard; 64],
+    king: [Bitboard; 64],
+    // pawn captures by color index
+    pawn: [[Bitboard; 64]; 2],
+    // cells up to the board edge by direction index
+    rays: [[Bitboard; 64]; 8],
+}
+
+impl AttackTables {
+    fn get() -> &'static AttackTables {
+        static ATTACK_TABLES: OnceLock<AttackTables> = OnceLock::new();
+        ATTACK_TABLES.get_or_init(AttackTables::new)
+    }
+
+    fn new() -> AttackTables {
+        let mut tables = AttackTables {
+            knight: [0; 64],
+            king: [0; 64],
+            pawn: [[0; 64]; 2],
+            rays: [[0; 64]; 8],
+        };
+        for square in 0..64 {
+            let (i, j) = ((square % 8) as i8, (square / 8) as i8);
+            tables.knight[square] = AttackTables::steps(i, j, &KNIGHT_STEPS);
+            tables.king[square] = AttackTables::steps(i, j, &KING_STEPS);
+            tables.pawn[0][square] = AttackTables::steps(i, j, &[(1, 1), (-1, 1)]);
+            tables.pawn[1][square] = AttackTables::steps(i, j, &[(1, -1), (-1, -1)]);
+            for (direction, (di, dj)) in RAY_DIRECTIONS.iter().enumerate() {
+                let (mut ray_i, mut ray_j) = (i + di, j + dj);
+                while AttackTables::is_on_board(ray_i, ray_j) {
+                    tables.rays[direction][square] |= 1 << (ray_j * BOARD_SIZE + ray_i);
+                    ray_i += di;
+                    ray_j += dj;
+                }
+            }
+        }
+        tables
+    }
+
+    fn steps(i: i8, j: i8, steps: &[(i8, i8)]) -> Bitboard {
+        steps
+            .iter()
+            .map(|(di, dj)| (i + di, j + dj))
+            .filter(|(i, j)| AttackTables::is_on_board(*i, *j))
+            .fold(0, |bitboard, (i, j)| bitboard | 1 << (j * BOARD_SIZE + i))
+    }
+
+    fn is_on_board(i: i8, j: i8) -> bool {
+        (0..BOARD_SIZE).contains(&i) && (0..BOARD_SIZE).contains(&j)
+    }
+
+    // ray cells up to and including the first occupied one
+    fn ray_attacks(&self, direction: usize, square: u32, occupied: Bitboard) -> Bitboard {
+        let ray = self.rays[direction][square as usize];
+        let blockers = ray & occupied;
+        if blockers == 0 {
+            return ray;
+        }
+        let (di, dj) = RAY_DIRECTIONS[direction];
+        let blocker = if dj * BOARD_SIZE + di > 0 {
+            blockers.trailing_zeros()
+        } else {
+            63 - blockers.leading_zeros()
+        };
+        ray & !self.rays[direction][blocker as usize]
+    }
+
+    fn rook_attacks(&self, square: u32, occupied: Bitboard) -> Bitboard {
+        (0..4).fold(0, |attacks, direction| {
+            attacks | self.ray_attacks(direction, square, occupied)
+        })
+    }
+
+    fn bishop_attacks(&self, square: u32, occupied: Bitboard) -> Bitboard {
+        (4..8).fold(0, |attacks, direction| {
+            attacks | self.ray_attacks(direction, square, occupied)
+        })
+    }
+}
+
+// pieces of a position as bitboards by color and by piece type
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BitboardPosition {
+    colors: [Bitboard; 2],
+    piece_types: [Bitboard; 6],
+}
+
+impl BitboardPosition {
+    pub fn new(pieces: &[&ChessPiece]) -> BitboardPosition {
+        let mut position = BitboardPosition {
+            colors: [0; 2],
+            piece_types: [0; 6],
+        };
+        for piece in pieces.iter() {
+            if let Some(square) = position.square(&piece.pos) {
+                position.colors[BitboardPosition::color_index(&piece.color)] |= 1 << square;
+                position.piece_types[BitboardPosition::piece_type_index(&piece.piece_type)] |=
+                    1 << square;
+            }
+        }
+        position
+    }
+
+    fn color_index(color: &ChessColor) -> usize {
+        match color {
+            ChessColor::WHITE => 0,
+            ChessColor::BLACK => 1,
+        }
+    }
+
+    fn piece_type_index(piece_type: &PieceType) -> usize {
+        match piece_type {
+            PieceType::PAWN => 0,
+            PieceType::BISHOP => 1,
+            PieceType::KNIGHT => 2,
+            PieceType::ROOK => 3,
+            PieceType::QUEEN => 4,
+            PieceType::KING => 5,
+        }
+    }
+
+    // bit index of the cell, None outside the board
+    pub fn square(&self, pos: &CellPosition) -> Option<u32> {
+        let (i, j) = (pos.i - FIRST_ELEMENT, pos.j - FIRST_ELEMENT);
+        AttackTables::is_on_board(i, j).then(|| (j * BOARD_SIZE + i) as u32)
+    }
+
+    pub fn cell(&self, square: u32) -> CellPosition {
+        CellPosition {
+            i: (square % 8) as i8 + FIRST_ELEMENT,
+            j: (square / 8) as i8 + FIRST_ELEMENT,
+        }
+    }
+
+    pub fn cells(&self, mut bitboard: Bitboard) -> Vec<CellPosition> {
+        let mut cells = Vec::with_capacity(bitboard.count_ones() as usize);
+        while bitboard != 0 {
+            cells.push(self.cell(bitboard.trailing_zeros()));
+            bitboard &= bitboard - 1;
+        }
+        cells
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.colors[0] | self.colors[1]
+    }
+
+    pub fn color_occupancy(&self, color: &ChessColor) -> Bitboard {
+        self.colors[BitboardPosition::color_index(color)]
+    }
+
+    pub fn pieces_of(&self, color: &ChessColor, piece_type: &PieceType) -> Bitboard {
+        self.color_occupancy(color)
+            & self.piece_types[BitboardPosition::piece_type_index(piece_type)]
+    }
+
+    pub fn is_occupied(&self, pos: &CellPosition) -> bool {
+        self.square(pos)
+            .is_some_and(|square| self.occupied() & 1 << square != 0)
+    }
+
+    // cells attacked by the piece standing on the square, pawns attack diagonally only
+    pub fn attacks(&self, piece_type: &PieceType, color: &ChessColor, square: u32) -> Bitboard {
+        let tables = AttackTables::get();
+        let occupied = self.occupied();
+        let square_index = square as usize;
+        match piece_type {
+            PieceType::PAWN => tables.pawn[BitboardPosition::color_index(color)][square_index],
+            PieceType::KNIGHT => tables.knight[square_index],
+            PieceType::KING => tables.king[square_index],
+            PieceType::BISHOP => tables.bishop_attacks(square, occupied),
+            PieceType::ROOK => tables.rook_attacks(square, occupied),
+            PieceType::QUEEN => {
+                tables.rook_attacks(square, occupied) | tables.bishop_attacks(square, occupied)
+            }
+        }
+    }
+
+    pub fn is_square_attacked(&self, square: u32, by_color: &ChessColor) -> bool {
+        let tables = AttackTables::get();
+        let occupied = self.occupied();
+        let square_index = square as usize;
+        let own_color_index = 1 - BitboardPosition::color_index(by_color);
+        let queens = self.pieces_of(by_color, &PieceType::QUEEN);
+
+        tables.pawn[own_color_index][square_index] & self.pieces_of(by_color, &PieceType::PAWN) != 0
+            || tables.knight[square_index] & self.pieces_of(by_color, &PieceType::KNIGHT) != 0
+            || tables.king[square_index] & self.pieces_of(by_color, &PieceType::KING) != 0
+            || tables.rook_attacks(square, occupied)
+                & (self.pieces_of(by_color, &PieceType::ROOK) | queens)
+                != 0
+            || tables.bishop_attacks(square, occupied)
+                & (self.pieces_of(by_color, &PieceType::BISHOP) | queens)
+                != 0
+    }
+
+    pub fn is_cell_attacked(&self, pos: &CellPosition, by_color: &ChessColor) -> bool {
+        self.square(pos)
+            .is_some_and(|square| self.is_square_attacked(square, by_color))
+    }
+
+    pub fn is_king_attacked(&self, color: &ChessColor) -> bool {
+        let king = self.pieces_of(color, &PieceType::KING);
+        king != 0 && self.is_square_attacked(king.trailing_zeros(), &color.opposite())
+    }
+
+    // position after moving the piece from one cell to another, a pawn moving
+    // diagonally to an empty cell captures en passant
+    pub fn after_move(&self, from: &CellPosition, to: &CellPosition) -> BitboardPosition {
+        let mut position = *self;
+        let (Some(from_square), Some(to_square)) = (self.square(from), self.square(to)) else {
+            return position;
+        };
+        let from_bit: Bitboard = 1 << from_square;
+        let to_bit: Bitboard = 1 << to_square;
+        let is_pawn = self.piece_types[0] & from_bit != 0;
+        if is_pawn && from.i != to.i && self.occupied() & to_bit == 0 {
+            let captured_cell = CellPosition { i: to.i, j: from.j };
+            if let Some(captured_square) = self.square(&captured_cell) {
+                position.clear(1 << captured_square);
+            }
+        }
+        position.clear(to_bit);
+        for bitboard in position
+            .colors
+            .iter_mut()
+            .chain(position.piece_types.iter_mut())
+        {
+            if *bitboard & from_bit != 0 {
+                *bitboard = *bitboard & !from_bit | to_bit;
+            }
+        }
+        position
+    }
+
+    fn clear(&mut self, bits: Bitboard) {
+        for bitboard in self.colors.iter_mut().chain(self.piece_types.iter_mut()) {
+            *bitboard &= !bits;
+        }
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use super::*;
+
+    fn cell(i: i8, j: i8) -> CellPosition {
+        CellPosition { i, j }
+    }
+
+    #[test]
+    fn test_bitboard_attack_tables() {
+        let tables = AttackTables::get();
+        assert_eq!(2, tables.knight[0].count_ones());
+        assert_eq!(8, tables.knight[27].count_ones());
+        assert_eq!(3, tables.king[63].count_ones());
+        assert_eq!(14, tables.rook_attacks(0, 0).count_ones());
+        assert_eq!(13, tables.bishop_attacks(27, 0).count_ones());
+    }
+
+    #[test]
+    fn test_bitboard_sliders_stop_at_blockers() {
+        let rook = ChessPiece::new(0, 0, ChessColor::WHITE, PieceType::ROOK);
+        let ally = ChessPiece::new(0, 3, ChessColor::WHITE, PieceType::PAWN);
+        let enemy = ChessPiece::new(5, 0, ChessColor::BLACK, PieceType::KNIGHT);
+        let position = BitboardPosition::new(&[&rook, &ally, &enemy]);
+
+        let attacks = position.attacks(&PieceType::ROOK, &ChessColor::WHITE, 0);
+        let mut cells = position.cells(attacks);
+        cells.sort_by_key(|cell| (cell.j, cell.i));
+        assert_eq!(
+            vec![
+                cell(1, 0),
+                cell(2, 0),
+                cell(3, 0),
+                cell(4, 0),
+                cell(5, 0),
+                cell(0, 1),
+                cell(0, 2),
+                cell(0, 3)
+            ],
+            cells
+        );
+    }
+
+    #[test]
+    fn test_bitboard_pawn_attacks_empty_cells() {
+        let pawn = ChessPiece::new(4, 6, ChessColor::BLACK, PieceType::PAWN);
+        let position = BitboardPosition::new(&[&pawn]);
+
+        assert!(position.is_cell_attacked(&cell(3, 5), &ChessColor::BLACK));
+        assert!(position.is_cell_attacked(&cell(5, 5), &ChessColor::BLACK));
+        assert!(!position.is_cell_attacked(&cell(4, 5), &ChessColor::BLACK));
+    }
+
+    #[test]
+    fn test_bitboard_en_passant_after_move() {
+        let pawn = ChessPiece::new(4, 4, ChessColor::WHITE, PieceType::PAWN);
+        let enemy_pawn = ChessPiece::new(3, 4, ChessColor::BLACK, PieceType::PAWN);
+        let position = BitboardPosition::new(&[&pawn, &enemy_pawn]);
+
+        let position = position.after_move(&cell(4, 4), &cell(3, 5));
+        assert_eq!(0, position.color_occupancy(&ChessColor::BLACK));
+        assert!(position.is_occupied(&cell(3, 5)));
+    }
+}
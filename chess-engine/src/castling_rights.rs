@@ -0,0 +1,96 @@
+use crate::{
+    chess_piece::{ChessPiece, PieceType},
+    common_chess::{CellPosition, ChessColor, FIRST_ELEMENT, LAST_ELEMENT},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CastlingSide {
+    // rook on the last file of the board
+    KING,
+    // rook on the first file of the board
+    QUEEN,
+}
+
+// castling availability, lost once the king or the corresponding rook has moved
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        CastlingRights {
+            white_king_side: true,
+            white_queen_side: true,
+            black_king_side: true,
+            black_queen_side: true,
+        }
+    }
+}
+
+impl CastlingRights {
+    pub fn none() -> CastlingRights {
+        CastlingRights {
+            white_king_side: false,
+            white_queen_side: false,
+            black_king_side: false,
+            black_queen_side: false,
+        }
+    }
+
+    pub fn is_allowed(&self, color: &ChessColor, side: CastlingSide) -> bool {
+        match (color, side) {
+            (ChessColor::WHITE, CastlingSide::KING) => self.white_king_side,
+            (ChessColor::WHITE, CastlingSide::QUEEN) => self.white_queen_side,
+            (ChessColor::BLACK, CastlingSide::KING) => self.black_king_side,
+            (ChessColor::BLACK, CastlingSide::QUEEN) => self.black_queen_side,
+        }
+    }
+
+    pub fn revoke(&mut self, color: &ChessColor, side: CastlingSide) {
+        match (color, side) {
+            (ChessColor::WHITE, CastlingSide::KING) => self.white_king_side = false,
+            (ChessColor::WHITE, CastlingSide::QUEEN) => self.white_queen_side = false,
+            (ChessColor::BLACK, CastlingSide::KING) => self.black_king_side = false,
+            (ChessColor::BLACK, CastlingSide::QUEEN) => self.black_queen_side = false,
+        }
+    }
+
+    // home cell of the rook for the given side
+    pub fn rook_cell(color: &ChessColor, side: CastlingSide) -> CellPosition {
+        let i = match side {
+            CastlingSide::KING => LAST_ELEMENT,
+            CastlingSide::QUEEN => FIRST_ELEMENT,
+        };
+        CellPosition {
+            i,
+            j: CastlingRights::home_rank(color),
+        }
+    }
+
+    pub fn home_rank(color: &ChessColor) -> i8 {
+        match color {
+            ChessColor::WHITE => FIRST_ELEMENT,
+            ChessColor::BLACK => LAST_ELEMENT,
+        }
+    }
+
+    // revoke rights touched by a move: the king or a rook leaving home, or a rook captured at home
+    pub fn update_after_move(&mut self, piece: &ChessPiece, to: &CellPosition) {
+        if piece.piece_type == PieceType::KING {
+            self.revoke(&piece.color, CastlingSide::KING);
+            self.revoke(&piece.color, CastlingSide::QUEEN);
+        }
+        for color in [ChessColor::WHITE, ChessColor::BLACK] {
+            for side in [CastlingSide::KING, CastlingSide::QUEEN] {
+                let rook_cell = CastlingRights::rook_cell(&color, side);
+                if rook_cell == piece.pos || rook_cell == *to {
+                    self.revoke(&color, side);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{
+    castling_rights::CastlingRights,
+    chess_piece::{ChessPiece, PieceType},
+    common_chess::CellPosition,
+};
+
+// a move made in the game, holds everything needed to take it back
+#[derive(Clone, Debug)]
+pub struct ChessMove {
+    // moved piece standing on its origin cell
+    pub piece: ChessPiece,
+    pub to: CellPosition,
+    // captured piece standing on its own cell, beside the destination for en passant
+    pub captured_piece: Option<ChessPiece>,
+    pub promotion: Option<PieceType>,
+    // origin and target cells of the rook moved by castling
+    pub castling_rook: Option<(CellPosition, CellPosition)>,
+    pub is_en_passant: bool,
+    // standard algebraic notation with the check or mate suffix
+    pub san: String,
+
+    // state before the move, restored by undo
+    pub previous_castling_rights: CastlingRights,
+    pub previous_en_passant_target: Option<CellPosition>,
+    pub previous_halfmove_clock: u32,
+}
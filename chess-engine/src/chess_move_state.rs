@@ -0,0 +1,404 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use log::warn;
+
+use crate::{
+    castling_rights::CastlingRights,
+    chess_move::ChessMove,
+    chess_piece::{ChessPiece, PieceType},
+    common_chess::{CellPosition, ChessColor},
+    fen_parser::FenParser,
+    notation::{Notation, PROMOTION_TYPES},
+};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DrawReason {
+    FiftyMoves,
+    ThreefoldRepetition,
+    InsufficientMaterial,
+    // both players agreed to it
+    Agreement,
+}
+
+// rules state of a game besides the pieces: side to move, castling and en passant
+// rights, clocks, check/mate/draw status and the move history
+#[derive(Default, Clone)]
+pub struct MoveState {
+    pub current_collor: ChessColor,
+
+    pub check_state: Option<ChessColor>,
+    pub mate_state: Option<ChessColor>,
+    pub is_stalemate_state: Option<ChessColor>,
+
+    pub castling_rights: CastlingRights,
+    // cell passed over by the last double pawn push
+    pub en_passant_target: Option<CellPosition>,
+    // cell of a pawn waiting for the promotion piece to be picked
+    pub promotion_cell: Option<CellPosition>,
+
+    // half moves since the last capture or pawn move
+    pub halfmove_clock: u32,
+    // half moves since the game start, white's first move is ply 0
+    pub ply_count: u32,
+    // hashes of every position reached in the game, the latest one last
+    pub position_history: Vec<u64>,
+    pub draw_state: Option<DrawReason>,
+    // side whose clock ran out
+    pub flag_state: Option<ChessColor>,
+    pub resign_state: Option<ChessColor>,
+
+    // FEN of the position before the first move
+    pub start_fen: Option<String>,
+    pub move_history: Vec<ChessMove>,
+    // moves taken back by undo, the next one to redo last
+    pub redo_history: Vec<ChessMove>,
+}
+
+impl MoveState {
+    pub fn next_move(&mut self) {
+        self.current_collor = self.current_collor.opposite();
+        self.ply_count += 1;
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.ply_count / 2 + 1
+    }
+
+    // ply of the first move in the history, ply_count only goes up once a made move
+    // is finished so it can't be counted back from it
+    pub fn start_ply(&self) -> u32 {
+        self.start_fen
+            .as_deref()
+            .and_then(|fen| FenParser::parse_fen(fen).ok())
+            .map_or(self.ply_count, |(_, start_state)| start_state.ply_count)
+    }
+
+    // applies a legal move of the side to move to the pieces and updates the state
+    // for the position after it, the turn itself passes with next_move;
+    // a promotion without the picked piece stays pending in promotion_cell
+    pub fn make_move(
+        &mut self,
+        pieces: &mut Vec<ChessPiece>,
+        from: &CellPosition,
+        to: &CellPosition,
+        promotion: Option<PieceType>,
+    ) -> Option<ChessMove> {
+        let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+        let piece = piece_refs
+            .iter()
+            .find(|cp| cp.pos == *from && cp.color == self.current_collor)
+            .map(|cp| (*cp).clone())?;
+        if !piece
+            .get_legal_cells_for_move(&piece_refs, self)
+            .contains(to)
+        {
+            return None;
+        }
+
+        if self.position_history.is_empty() {
+            let current_color = self.current_collor.clone();
+            self.record_position(&current_color, &piece_refs);
+            self.start_fen = Some(FenParser::save_fen(&piece_refs, self));
+        }
+        let san = Notation::move_to_san(&piece, to, &piece_refs, self);
+        let mut cloned_selected_piece = piece.clone();
+        let captured_piece =
+            ChessPiece::pieces_after_move(&piece_refs, to, &mut cloned_selected_piece)
+                .0
+                .cloned();
+        let castling_rook = piece.castling_rook_move(to);
+
+        if let Some(captured_piece) = &captured_piece {
+            pieces.retain(|cp| cp.pos != captured_piece.pos);
+        }
+        for cp in pieces.iter_mut() {
+            if cp.pos == *from {
+                cp.pos = *to;
+                if let Some(piece_type) = promotion.as_ref().filter(|_| piece.is_promotion_move(to))
+                {
+                    cp.piece_type = piece_type.clone();
+                }
+            } else if let Some((_, rook_to)) =
+                castling_rook.filter(|(rook_from, _)| cp.pos == *rook_from)
+            {
+                cp.pos = rook_to;
+            }
+        }
+
+        let is_en_passant = captured_piece
+            .as_ref()
+            .is_some_and(|captured_piece| captured_piece.pos != *to);
+        let chess_move = ChessMove {
+            piece: piece.clone(),
+            to: *to,
+            captured_piece,
+            promotion: None,
+            castling_rook,
+            is_en_passant,
+            san,
+            previous_castling_rights: self.castling_rights.clone(),
+            previous_en_passant_target: self.en_passant_target,
+            previous_halfmove_clock: self.halfmove_clock,
+        };
+        self.castling_rights.update_after_move(&piece, to);
+        self.en_passant_target = piece.en_passant_target_after_move(to);
+        self.update_halfmove_clock(&piece, chess_move.captured_piece.is_some());
+        self.move_history.push(chess_move);
+        self.redo_history.clear();
+
+        let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+        match promotion.filter(|_| piece.is_promotion_move(to)) {
+            Some(piece_type) => self.complete_promotion(&piece_type, &piece_refs),
+            None if piece.is_promotion_move(to) => {
+                // check state of the pawn move until the promotion piece is picked
+                self.update_check_state(&piece.color.opposite(), &piece_refs);
+                self.promotion_cell = Some(*to);
+            }
+            None => {
+                let color = piece.color.opposite();
+                self.update_check_state(&color, &piece_refs);
+                self.update_draw_state(&color, &piece_refs);
+                let check_suffix = Notation::check_suffix(self);
+                if let Some(last_move) = self.move_history.last_mut() {
+                    last_move.san.push_str(check_suffix);
+                }
+            }
+        }
+        self.move_history.last().cloned()
+    }
+
+    // whether make_move makes the move whole: it is legal for the side to move and
+    // a pawn reaching the last rank has its promotion piece
+    pub fn is_complete_move(
+        &self,
+        pieces: &[ChessPiece],
+        from: &CellPosition,
+        to: &CellPosition,
+        promotion: &Option<PieceType>,
+    ) -> bool {
+        let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+        let Some(piece) = piece_refs
+            .iter()
+            .find(|cp| cp.pos == *from && cp.color == self.current_collor)
+        else {
+            return false;
+        };
+        if piece.is_promotion_move(to)
+            && !promotion
+                .as_ref()
+                .is_some_and(|piece_type| PROMOTION_TYPES.contains(piece_type))
+        {
+            return false;
+        }
+        piece
+            .get_legal_cells_for_move(&piece_refs, self)
+            .contains(to)
+    }
+
+    // takes the last move back on the pieces and restores the state before it,
+    // the move is kept for redo
+    pub fn undo_move(&mut self, pieces: &mut Vec<ChessPiece>) -> Option<ChessMove> {
+        if self.promotion_cell.is_some() {
+            return None;
+        }
+        let chess_move = self.move_history.pop()?;
+
+        for cp in pieces.iter_mut() {
+            if cp.pos == chess_move.to {
+                *cp = chess_move.piece.clone();
+            } else if let Some((rook_from, _)) = chess_move
+                .castling_rook
+                .filter(|(_, rook_to)| cp.pos == *rook_to)
+            {
+                cp.pos = rook_from;
+            }
+        }
+        if let Some(captured_piece) = &chess_move.captured_piece {
+            pieces.push(captured_piece.clone());
+        }
+
+        self.castling_rights = chess_move.previous_castling_rights.clone();
+        self.en_passant_target = chess_move.previous_en_passant_target;
+        self.halfmove_clock = chess_move.previous_halfmove_clock;
+        self.position_history.pop();
+        self.draw_state = None;
+        self.flag_state = None;
+        self.resign_state = None;
+        self.current_collor = chess_move.piece.color.clone();
+        self.ply_count = self.ply_count.saturating_sub(1);
+
+        let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+        let color = self.current_collor.clone();
+        self.update_check_state(&color, &piece_refs);
+
+        self.redo_history.push(chess_move.clone());
+        Some(chess_move)
+    }
+
+    // makes the last undone move again and passes the turn
+    pub fn redo_move(&mut self, pieces: &mut Vec<ChessPiece>) -> Option<ChessMove> {
+        if self.promotion_cell.is_some() {
+            return None;
+        }
+        let undone_move = self.redo_history.pop()?;
+        let redo_history = std::mem::take(&mut self.redo_history);
+        let chess_move = self.make_move(
+            pieces,
+            &undone_move.piece.pos,
+            &undone_move.to,
+            undone_move.promotion.clone(),
+        );
+        self.redo_history = redo_history;
+        match chess_move {
+            Some(_) => self.next_move(),
+            None => self.redo_history.push(undone_move),
+        }
+        chess_move
+    }
+
+    // finishes the last move once the pawn on the last rank is replaced by the picked piece
+    pub fn complete_promotion(&mut self, piece_type: &PieceType, pieces: &Vec<&ChessPiece>) {
+        let color = self.current_collor.opposite();
+        self.update_check_state(&color, pieces);
+        self.update_draw_state(&color, pieces);
+        self.promotion_cell = None;
+        let check_suffix = Notation::check_suffix(self);
+        if let Some(last_move) = self.move_history.last_mut() {
+            last_move.promotion = Some(piece_type.clone());
+            last_move
+                .san
+                .push_str(&Notation::promotion_suffix(piece_type));
+            last_move.san.push_str(check_suffix);
+        }
+    }
+
+    // check, mate and stalemate state of the given side for the pieces after a move
+    pub fn update_check_state(&mut self, color: &ChessColor, pieces: &Vec<&ChessPiece>) {
+        self.check_state = None;
+        self.mate_state = None;
+        self.is_stalemate_state = None;
+        if ChessPiece::is_king_under_check(color, pieces) {
+            if ChessPiece::is_king_under_mate(color, pieces, self) {
+                warn!("king mate state");
+                self.mate_state = Some(color.clone());
+            } else {
+                self.check_state = Some(color.clone());
+            }
+        } else if !ChessPiece::has_legal_moves(color, pieces, self) {
+            warn!("stalemate state");
+            self.is_stalemate_state = Some(color.clone());
+        }
+    }
+
+    // cells the last played move was made from and to
+    pub fn last_move_cells(&self) -> Option<(CellPosition, CellPosition)> {
+        self.move_history
+            .last()
+            .map(|chess_move| (chess_move.piece.pos, chess_move.to))
+    }
+
+    // cell of the king in check or mated, if any
+    pub fn checked_king_cell(&self, pieces: &[&ChessPiece]) -> Option<CellPosition> {
+        let color = self.check_state.as_ref().or(self.mate_state.as_ref())?;
+        pieces
+            .iter()
+            .find(|cp| cp.color == *color && cp.piece_type == PieceType::KING)
+            .map(|cp| cp.pos)
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.mate_state.is_some()
+            || self.is_stalemate_state.is_some()
+            || self.draw_state.is_some()
+            || self.flag_state.is_some()
+            || self.resign_state.is_some()
+    }
+
+    // PGN result of the game, "*" while it is still going on
+    pub fn result(&self) -> &'static str {
+        let loser = self
+            .mate_state
+            .as_ref()
+            .or(self.flag_state.as_ref())
+            .or(self.resign_state.as_ref());
+        match (loser, self.is_game_over()) {
+            (Some(ChessColor::BLACK), _) => "1-0",
+            (Some(ChessColor::WHITE), _) => "0-1",
+            (None, true) => "1/2-1/2",
+            (None, false) => "*",
+        }
+    }
+
+    pub fn update_halfmove_clock(&mut self, moved_piece: &ChessPiece, is_capture: bool) {
+        if moved_piece.piece_type == PieceType::PAWN || is_capture {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+    }
+
+    // records the position after a move and declares a draw if a draw rule applies,
+    // a mate on the same move takes precedence
+    pub fn update_draw_state(&mut self, side_to_move: &ChessColor, pieces: &Vec<&ChessPiece>) {
+        self.record_position(side_to_move, pieces);
+        if self.mate_state.is_some() || self.is_stalemate_state.is_some() {
+            return;
+        }
+        let position_hash = self.position_hash(side_to_move, pieces);
+        let repetitions = self
+            .position_history
+            .iter()
+            .filter(|hash| **hash == position_hash)
+            .count();
+
+        self.draw_state = if self.halfmove_clock >= 100 {
+            Some(DrawReason::FiftyMoves)
+        } else if repetitions >= 3 {
+            Some(DrawReason::ThreefoldRepetition)
+        } else if ChessPiece::is_insufficient_material(pieces) {
+            Some(DrawReason::InsufficientMaterial)
+        } else {
+            None
+        };
+    }
+
+    pub fn record_position(&mut self, side_to_move: &ChessColor, pieces: &Vec<&ChessPiece>) {
+        let position_hash = self.position_hash(side_to_move, pieces);
+        self.position_history.push(position_hash);
+    }
+
+    // positions are equal when pieces, side to move, castling and usable en passant rights match
+    fn position_hash(&self, side_to_move: &ChessColor, pieces: &Vec<&ChessPiece>) -> u64 {
+        let mut sorted_pieces: Vec<&ChessPiece> = pieces.to_vec();
+        sorted_pieces.sort_by_key(|piece| (piece.pos.j, piece.pos.i));
+
+        let mut hasher = DefaultHasher::new();
+        for piece in sorted_pieces {
+            piece.pos.hash(&mut hasher);
+            piece.color.hash(&mut hasher);
+            piece.piece_type.hash(&mut hasher);
+        }
+        side_to_move.hash(&mut hasher);
+        self.castling_rights.hash(&mut hasher);
+        self.en_passant_target
+            .filter(|target| self.can_capture_en_passant(side_to_move, target, pieces))
+            .hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // the en passant right only tells positions apart when a pawn can use it
+    fn can_capture_en_passant(
+        &self,
+        side_to_move: &ChessColor,
+        target: &CellPosition,
+        pieces: &Vec<&ChessPiece>,
+    ) -> bool {
+        pieces
+            .iter()
+            .filter(|cp| cp.color == *side_to_move && cp.piece_type == PieceType::PAWN)
+            .any(|cp| cp.get_legal_cells_for_move(pieces, self).contains(target))
+    }
+}
@@ -0,0 +1,719 @@
+use crate::{
+    bitboard::{Bitboard, BitboardPosition},
+    castling_rights::{CastlingRights, CastlingSide},
+    chess_move_state::MoveState,
+    common_chess::{CellPosition, ChessColor, FIRST_ELEMENT, LAST_ELEMENT},
+};
+
+#[derive(Clone, PartialEq, Debug, Eq, Hash)]
+pub enum PieceType {
+    PAWN,
+    BISHOP,
+    KNIGHT,
+    ROOK,
+    QUEEN,
+    KING,
+}
+
+#[derive(Clone, Debug)]
+pub struct ChessPiece {
+    pub pos: CellPosition,
+    pub color: ChessColor,
+    pub piece_type: PieceType,
+}
+impl ChessPiece {
+    pub fn new(i: i8, j: i8, color: ChessColor, piece_type: PieceType) -> ChessPiece {
+        let pos = CellPosition { i, j };
+        ChessPiece {
+            pos,
+            color,
+            piece_type,
+        }
+    }
+
+    pub fn get_available_cells_for_move(
+        &self,
+        pieces: &Vec<&ChessPiece>,
+        move_state: &MoveState,
+    ) -> Vec<CellPosition> {
+        let position = BitboardPosition::new(pieces);
+        self.available_cells_in(&position, move_state)
+    }
+
+    fn available_cells_in(
+        &self,
+        position: &BitboardPosition,
+        move_state: &MoveState,
+    ) -> Vec<CellPosition> {
+        let mut cells = position.cells(self.available_moves(position));
+        match self.piece_type {
+            PieceType::KING => {
+                cells.extend(self.available_castling_cells(position, &move_state.castling_rights))
+            }
+            PieceType::PAWN => cells
+                .extend(self.available_en_passant_cell(position, &move_state.en_passant_target)),
+            _ => {}
+        }
+        cells
+    }
+
+    // moves by the piece's own movement pattern, without castling and en passant
+    fn available_moves(&self, position: &BitboardPosition) -> Bitboard {
+        let Some(square) = position.square(&self.pos) else {
+            return 0;
+        };
+        let attacks = position.attacks(&self.piece_type, &self.color, square);
+        match self.piece_type {
+            PieceType::PAWN => {
+                self.pawn_pushes(position)
+                    | attacks & position.color_occupancy(&self.color.opposite())
+            }
+            _ => attacks & !position.color_occupancy(&self.color),
+        }
+    }
+
+    fn pawn_pushes(&self, position: &BitboardPosition) -> Bitboard {
+        let is_first_move = (self.color == ChessColor::WHITE && self.pos.j == FIRST_ELEMENT + 1)
+            || (self.color == ChessColor::BLACK && self.pos.j == LAST_ELEMENT - 1);
+        let max_steps = if is_first_move { 2 } else { 1 };
+
+        let mut pushes = 0;
+        for step in 1..=max_steps {
+            let cell = CellPosition {
+                i: self.pos.i,
+                j: self.pos.j + step * self.pawn_direction(),
+            };
+            let Some(square) = position
+                .square(&cell)
+                .filter(|_| !position.is_occupied(&cell))
+            else {
+                break;
+            };
+            pushes |= 1 << square;
+        }
+        pushes
+    }
+
+    fn pawn_direction(&self) -> i8 {
+        match self.color {
+            ChessColor::WHITE => 1,
+            ChessColor::BLACK => -1,
+        }
+    }
+
+    fn available_en_passant_cell(
+        &self,
+        position: &BitboardPosition,
+        en_passant_target: &Option<CellPosition>,
+    ) -> Option<CellPosition> {
+        let target = (*en_passant_target)?;
+        if target.j != self.pos.j + self.pawn_direction() || (target.i - self.pos.i).abs() != 1 {
+            return None;
+        }
+        let captured_cell = CellPosition {
+            i: target.i,
+            j: self.pos.j,
+        };
+        let enemy_pawns = position.pieces_of(&self.color.opposite(), &PieceType::PAWN);
+        position
+            .square(&captured_cell)
+            .filter(|square| enemy_pawns & 1 << square != 0)
+            .map(|_| target)
+    }
+
+    pub fn is_promotion_move(&self, cell_position: &CellPosition) -> bool {
+        let last_rank = match self.color {
+            ChessColor::WHITE => LAST_ELEMENT,
+            ChessColor::BLACK => FIRST_ELEMENT,
+        };
+        self.piece_type == PieceType::PAWN && cell_position.j == last_rank
+    }
+
+    // cell passed over by a pawn double push, None for any other move
+    pub fn en_passant_target_after_move(
+        &self,
+        cell_position: &CellPosition,
+    ) -> Option<CellPosition> {
+        if self.piece_type != PieceType::PAWN || (cell_position.j - self.pos.j).abs() != 2 {
+            return None;
+        }
+        Some(CellPosition {
+            i: self.pos.i,
+            j: (self.pos.j + cell_position.j) / 2,
+        })
+    }
+
+    fn available_castling_cells(
+        &self,
+        position: &BitboardPosition,
+        castling_rights: &CastlingRights,
+    ) -> Vec<CellPosition> {
+        let mut cells = Vec::new();
+        let enemy_color = self.color.opposite();
+        if self.pos.j != CastlingRights::home_rank(&self.color) {
+            return cells;
+        }
+        if position.is_cell_attacked(&self.pos, &enemy_color) {
+            return cells;
+        }
+        let rooks = position.pieces_of(&self.color, &PieceType::ROOK);
+        for side in [CastlingSide::KING, CastlingSide::QUEEN] {
+            if !castling_rights.is_allowed(&self.color, side) {
+                continue;
+            }
+            let rook_cell = CastlingRights::rook_cell(&self.color, side);
+            let has_rook = position
+                .square(&rook_cell)
+                .is_some_and(|square| rooks & 1 << square != 0);
+            if !has_rook || (rook_cell.i - self.pos.i).abs() < 3 {
+                continue;
+            }
+            let direction = (rook_cell.i - self.pos.i).signum();
+            let is_path_empty = (1..(rook_cell.i - self.pos.i).abs()).all(|step| {
+                !position.is_occupied(&CellPosition {
+                    i: self.pos.i + step * direction,
+                    j: self.pos.j,
+                })
+            });
+            if !is_path_empty {
+                continue;
+            }
+            let passed_cell = CellPosition {
+                i: self.pos.i + direction,
+                j: self.pos.j,
+            };
+            let target_cell = CellPosition {
+                i: self.pos.i + 2 * direction,
+                j: self.pos.j,
+            };
+            let is_path_attacked = [passed_cell, target_cell]
+                .iter()
+                .any(|cell| position.is_cell_attacked(cell, &enemy_color));
+            if !is_path_attacked {
+                cells.push(target_cell);
+            }
+        }
+        cells
+    }
+
+    // rook (from, to) cells if moving the king to the cell is castling
+    pub fn castling_rook_move(
+        &self,
+        cell_position: &CellPosition,
+    ) -> Option<(CellPosition, CellPosition)> {
+        if self.piece_type != PieceType::KING
+            || self.pos.j != cell_position.j
+            || (cell_position.i - self.pos.i).abs() != 2
+        {
+            return None;
+        }
+        let direction = (cell_position.i - self.pos.i).signum();
+        let side = if direction > 0 {
+            CastlingSide::KING
+        } else {
+            CastlingSide::QUEEN
+        };
+        let rook_from = CastlingRights::rook_cell(&self.color, side);
+        let rook_to = CellPosition {
+            i: self.pos.i + direction,
+            j: self.pos.j,
+        };
+        Some((rook_from, rook_to))
+    }
+
+    pub fn pieces_after_move<'a>(
+        pieces: &Vec<&'a ChessPiece>,
+        cell_position: &CellPosition,
+        cloned_selected_piece: &'a mut ChessPiece,
+    ) -> (Option<&'a ChessPiece>, Vec<&'a ChessPiece>) {
+        // a pawn moving diagonally to an empty cell captures en passant
+        let is_en_passant = cloned_selected_piece.piece_type == PieceType::PAWN
+            && cloned_selected_piece.pos.i != cell_position.i;
+        let maybe_removed_piece = pieces
+            .iter()
+            .find(|chess_piece| chess_piece.pos.eq(cell_position))
+            .or_else(|| {
+                pieces.iter().find(|chess_piece| {
+                    is_en_passant
+                        && chess_piece.pos.i == cell_position.i
+                        && chess_piece.pos.j == cloned_selected_piece.pos.j
+                })
+            })
+            .copied();
+
+        let mut pieces_after_move: Vec<&ChessPiece> = pieces
+            .iter()
+            .filter(|piece| piece.pos != cloned_selected_piece.pos)
+            .filter(|piece| {
+                maybe_removed_piece
+                    .filter(|rm_piece| piece.pos == rm_piece.pos)
+                    .is_none()
+            })
+            .copied()
+            .collect();
+
+        cloned_selected_piece.pos = *cell_position;
+        pieces_after_move.push(cloned_selected_piece);
+        (maybe_removed_piece, pieces_after_move)
+    }
+
+    pub fn is_king_under_check(color: &ChessColor, pieces: &Vec<&ChessPiece>) -> bool {
+        BitboardPosition::new(pieces).is_king_attacked(color)
+    }
+
+    // no legal move is left for the given side
+    pub fn is_king_under_mate(
+        color: &ChessColor,
+        pieces: &Vec<&ChessPiece>,
+        move_state: &MoveState,
+    ) -> bool {
+        !ChessPiece::has_legal_moves(color, pieces, move_state)
+    }
+
+    // dead positions: kings only, a single minor piece, or bishops on one cell color
+    pub fn is_insufficient_material(pieces: &Vec<&ChessPiece>) -> bool {
+        let non_king_pieces: Vec<&ChessPiece> = pieces
+            .iter()
+            .filter(|piece| piece.piece_type != PieceType::KING)
+            .copied()
+            .collect();
+        match non_king_pieces.as_slice() {
+            [] => true,
+            [piece] => matches!(piece.piece_type, PieceType::BISHOP | PieceType::KNIGHT),
+            [first, ..] => non_king_pieces.iter().all(|piece| {
+                piece.piece_type == PieceType::BISHOP
+                    && (piece.pos.i + piece.pos.j) % 2 == (first.pos.i + first.pos.j) % 2
+            }),
+        }
+    }
+
+    // available cells that do not leave the own king under check
+    pub fn get_legal_cells_for_move(
+        &self,
+        pieces: &Vec<&ChessPiece>,
+        move_state: &MoveState,
+    ) -> Vec<CellPosition> {
+        let position = BitboardPosition::new(pieces);
+        self.get_available_cells_for_move(pieces, move_state)
+            .into_iter()
+            .filter(|cell_position| !self.is_leaving_king_under_check(&position, cell_position))
+            .collect()
+    }
+
+    fn is_leaving_king_under_check(
+        &self,
+        position: &BitboardPosition,
+        cell_position: &CellPosition,
+    ) -> bool {
+        position
+            .after_move(&self.pos, cell_position)
+            .is_king_attacked(&self.color)
+    }
+
+    fn legal_cells_in(
+        &self,
+        position: &BitboardPosition,
+        move_state: &MoveState,
+    ) -> Vec<CellPosition> {
+        self.available_cells_in(position, move_state)
+            .into_iter()
+            .filter(|cell_position| !self.is_leaving_king_under_check(position, cell_position))
+            .collect()
+    }
+
+    pub fn has_legal_moves(
+        color: &ChessColor,
+        pieces: &Vec<&ChessPiece>,
+        move_state: &MoveState,
+    ) -> bool {
+        let position = BitboardPosition::new(pieces);
+        pieces
+            .iter()
+            .filter(|piece| piece.color.eq(color))
+            .any(|piece| !piece.legal_cells_in(&position, move_state).is_empty())
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+
+    use super::*;
+    use crate::{chess_move_state::DrawReason, fen_parser::FenParser, notation::Notation};
+
+    #[test]
+    fn test_chess_piece_king_mate_true() {
+        let rook1 = ChessPiece::new(0, 7, ChessColor::WHITE, PieceType::ROOK);
+        let rook2 = ChessPiece::new(0, 6, ChessColor::WHITE, PieceType::ROOK);
+        let king = ChessPiece::new(5, 7, ChessColor::BLACK, PieceType::KING);
+
+        let pieces = vec![&rook1, &rook2, &king];
+        let is_mate =
+            ChessPiece::is_king_under_mate(&ChessColor::BLACK, &pieces, &MoveState::default());
+        assert!(is_mate);
+    }
+
+    #[test]
+    fn test_chess_piece_king_mate_false() {
+        let rook1 = ChessPiece::new(0, 7, ChessColor::WHITE, PieceType::ROOK);
+        let rook2 = ChessPiece::new(0, 6, ChessColor::WHITE, PieceType::ROOK);
+        let king = ChessPiece::new(5, 6, ChessColor::BLACK, PieceType::KING);
+
+        let pieces = vec![&rook1, &rook2, &king];
+        let is_mate =
+            ChessPiece::is_king_under_mate(&ChessColor::BLACK, &pieces, &MoveState::default());
+        assert!(!is_mate);
+    }
+
+    #[test]
+    fn test_chess_piece_king_castling_available() {
+        let king = ChessPiece::new(4, 0, ChessColor::WHITE, PieceType::KING);
+        let rook1 = ChessPiece::new(0, 0, ChessColor::WHITE, PieceType::ROOK);
+        let rook2 = ChessPiece::new(7, 0, ChessColor::WHITE, PieceType::ROOK);
+
+        let pieces = vec![&king, &rook1, &rook2];
+        let cells = king.get_available_cells_for_move(&pieces, &MoveState::default());
+        assert!(cells.contains(&CellPosition { i: 6, j: 0 }));
+        assert!(cells.contains(&CellPosition { i: 2, j: 0 }));
+        assert_eq!(
+            Some((CellPosition { i: 7, j: 0 }, CellPosition { i: 5, j: 0 })),
+            king.castling_rook_move(&CellPosition { i: 6, j: 0 })
+        );
+
+        let move_state = MoveState {
+            castling_rights: CastlingRights::none(),
+            ..Default::default()
+        };
+        let cells = king.get_available_cells_for_move(&pieces, &move_state);
+        assert!(!cells.contains(&CellPosition { i: 6, j: 0 }));
+        assert!(!cells.contains(&CellPosition { i: 2, j: 0 }));
+    }
+
+    #[test]
+    fn test_chess_piece_king_castling_through_check() {
+        let king = ChessPiece::new(4, 0, ChessColor::WHITE, PieceType::KING);
+        let rook1 = ChessPiece::new(0, 0, ChessColor::WHITE, PieceType::ROOK);
+        let rook2 = ChessPiece::new(7, 0, ChessColor::WHITE, PieceType::ROOK);
+        let enemy_rook = ChessPiece::new(5, 7, ChessColor::BLACK, PieceType::ROOK);
+
+        let pieces = vec![&king, &rook1, &rook2, &enemy_rook];
+        let cells = king.get_available_cells_for_move(&pieces, &MoveState::default());
+        assert!(!cells.contains(&CellPosition { i: 6, j: 0 }));
+        assert!(cells.contains(&CellPosition { i: 2, j: 0 }));
+    }
+
+    #[test]
+    fn test_chess_piece_king_castling_out_of_check() {
+        let king = ChessPiece::new(4, 0, ChessColor::WHITE, PieceType::KING);
+        let rook1 = ChessPiece::new(0, 0, ChessColor::WHITE, PieceType::ROOK);
+        let rook2 = ChessPiece::new(7, 0, ChessColor::WHITE, PieceType::ROOK);
+        let enemy_rook = ChessPiece::new(4, 7, ChessColor::BLACK, PieceType::ROOK);
+
+        let pieces = vec![&king, &rook1, &rook2, &enemy_rook];
+        let cells = king.get_available_cells_for_move(&pieces, &MoveState::default());
+        assert!(!cells.contains(&CellPosition { i: 6, j: 0 }));
+        assert!(!cells.contains(&CellPosition { i: 2, j: 0 }));
+    }
+
+    #[test]
+    fn test_chess_piece_pawn_en_passant() {
+        let pawn = ChessPiece::new(4, 4, ChessColor::WHITE, PieceType::PAWN);
+        let enemy_pawn = ChessPiece::new(3, 4, ChessColor::BLACK, PieceType::PAWN);
+
+        let pieces = vec![&pawn, &enemy_pawn];
+        let en_passant_target = CellPosition { i: 3, j: 5 };
+        let cells = pawn.get_available_cells_for_move(&pieces, &MoveState::default());
+        assert!(!cells.contains(&en_passant_target));
+
+        let move_state = MoveState {
+            en_passant_target: Some(en_passant_target),
+            ..Default::default()
+        };
+        let cells = pawn.get_available_cells_for_move(&pieces, &move_state);
+        assert!(cells.contains(&en_passant_target));
+
+        let mut cloned_pawn = pawn.clone();
+        let (removed_piece, pieces_after_move) =
+            ChessPiece::pieces_after_move(&pieces, &en_passant_target, &mut cloned_pawn);
+        assert_eq!(Some(enemy_pawn.pos), removed_piece.map(|cp| cp.pos));
+        assert_eq!(1, pieces_after_move.len());
+    }
+
+    #[test]
+    fn test_chess_piece_pawn_en_passant_target_after_move() {
+        let pawn = ChessPiece::new(3, 6, ChessColor::BLACK, PieceType::PAWN);
+        assert_eq!(
+            Some(CellPosition { i: 3, j: 5 }),
+            pawn.en_passant_target_after_move(&CellPosition { i: 3, j: 4 })
+        );
+        assert_eq!(
+            None,
+            pawn.en_passant_target_after_move(&CellPosition { i: 3, j: 5 })
+        );
+    }
+
+    #[test]
+    fn test_chess_piece_pawn_blocked_double_push() {
+        let pawn = ChessPiece::new(4, 1, ChessColor::WHITE, PieceType::PAWN);
+        let knight = ChessPiece::new(4, 2, ChessColor::BLACK, PieceType::KNIGHT);
+        let pieces = vec![&pawn, &knight];
+
+        let cells = pawn.get_available_cells_for_move(&pieces, &MoveState::default());
+        assert!(cells.is_empty());
+    }
+
+    #[test]
+    fn test_chess_piece_pawn_promotion_move() {
+        let white_pawn = ChessPiece::new(2, 6, ChessColor::WHITE, PieceType::PAWN);
+        let black_pawn = ChessPiece::new(2, 1, ChessColor::BLACK, PieceType::PAWN);
+
+        assert!(white_pawn.is_promotion_move(&CellPosition { i: 2, j: 7 }));
+        assert!(black_pawn.is_promotion_move(&CellPosition { i: 2, j: 0 }));
+        assert!(!black_pawn.is_promotion_move(&CellPosition { i: 2, j: 7 }));
+    }
+
+    #[test]
+    fn test_chess_piece_legal_cells_exclude_pinned_moves() {
+        let king = ChessPiece::new(4, 0, ChessColor::WHITE, PieceType::KING);
+        let bishop = ChessPiece::new(4, 1, ChessColor::WHITE, PieceType::BISHOP);
+        let enemy_rook = ChessPiece::new(4, 7, ChessColor::BLACK, PieceType::ROOK);
+
+        let pieces = vec![&king, &bishop, &enemy_rook];
+        let move_state = MoveState {
+            castling_rights: CastlingRights::none(),
+            ..Default::default()
+        };
+        assert!(!bishop
+            .get_available_cells_for_move(&pieces, &move_state)
+            .is_empty());
+        assert!(bishop
+            .get_legal_cells_for_move(&pieces, &move_state)
+            .is_empty());
+
+        let king_cells = king.get_legal_cells_for_move(&pieces, &move_state);
+        assert!(!king_cells.contains(&CellPosition { i: 4, j: 1 }));
+        assert!(king_cells.contains(&CellPosition { i: 3, j: 0 }));
+    }
+
+    #[test]
+    fn test_move_state_stalemate() {
+        let queen = ChessPiece::new(5, 6, ChessColor::WHITE, PieceType::QUEEN);
+        let king = ChessPiece::new(5, 5, ChessColor::WHITE, PieceType::KING);
+        let enemy_king = ChessPiece::new(7, 7, ChessColor::BLACK, PieceType::KING);
+
+        let pieces = vec![&queen, &king, &enemy_king];
+        let mut move_state = MoveState::default();
+        move_state.update_check_state(&ChessColor::BLACK, &pieces);
+        assert_eq!(Some(ChessColor::BLACK), move_state.is_stalemate_state);
+        assert_eq!(None, move_state.mate_state);
+        assert!(move_state.is_game_over());
+    }
+
+    #[test]
+    fn test_chess_piece_insufficient_material() {
+        let king = ChessPiece::new(4, 0, ChessColor::WHITE, PieceType::KING);
+        let enemy_king = ChessPiece::new(4, 7, ChessColor::BLACK, PieceType::KING);
+        let bishop = ChessPiece::new(2, 0, ChessColor::WHITE, PieceType::BISHOP);
+        let same_color_bishop = ChessPiece::new(5, 7, ChessColor::BLACK, PieceType::BISHOP);
+        let other_color_bishop = ChessPiece::new(2, 7, ChessColor::BLACK, PieceType::BISHOP);
+        let pawn = ChessPiece::new(0, 1, ChessColor::WHITE, PieceType::PAWN);
+
+        assert!(ChessPiece::is_insufficient_material(&vec![
+            &king,
+            &enemy_king
+        ]));
+        assert!(ChessPiece::is_insufficient_material(&vec![
+            &king,
+            &enemy_king,
+            &bishop
+        ]));
+        assert!(ChessPiece::is_insufficient_material(&vec![
+            &king,
+            &enemy_king,
+            &bishop,
+            &same_color_bishop
+        ]));
+        assert!(!ChessPiece::is_insufficient_material(&vec![
+            &king,
+            &enemy_king,
+            &bishop,
+            &other_color_bishop
+        ]));
+        assert!(!ChessPiece::is_insufficient_material(&vec![
+            &king,
+            &enemy_king,
+            &pawn
+        ]));
+    }
+
+    #[test]
+    fn test_move_state_threefold_repetition() {
+        let king = ChessPiece::new(4, 0, ChessColor::WHITE, PieceType::KING);
+        let rook = ChessPiece::new(0, 0, ChessColor::WHITE, PieceType::ROOK);
+        let enemy_king = ChessPiece::new(4, 7, ChessColor::BLACK, PieceType::KING);
+        let pieces = vec![&king, &rook, &enemy_king];
+        let mut move_state = MoveState {
+            castling_rights: CastlingRights::none(),
+            ..Default::default()
+        };
+
+        move_state.record_position(&ChessColor::WHITE, &pieces);
+        move_state.update_draw_state(&ChessColor::WHITE, &pieces);
+        assert_eq!(None, move_state.draw_state);
+        move_state.update_draw_state(&ChessColor::WHITE, &pieces);
+        assert_eq!(Some(DrawReason::ThreefoldRepetition), move_state.draw_state);
+    }
+
+    #[test]
+    fn test_move_state_repetition_ignores_unusable_en_passant() {
+        let (mut pieces, mut move_state) =
+            FenParser::parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+                .unwrap();
+        let cell = |name: &str| Notation::cell_from_string(name).unwrap();
+        let mut play = |from: &str, to: &str| {
+            move_state
+                .make_move(&mut pieces, &cell(from), &cell(to), None)
+                .unwrap();
+            move_state.next_move();
+            move_state.draw_state.clone()
+        };
+
+        // no black pawn can take on e3, so the position after 1. e4 comes back with the knights
+        play("e2", "e4");
+        for _ in 0..3 {
+            assert_eq!(None, play("g8", "f6"));
+            assert_eq!(None, play("g1", "f3"));
+            assert_eq!(None, play("f6", "g8"));
+            if play("f3", "g1").is_some() {
+                break;
+            }
+        }
+        assert_eq!(5, move_state.fullmove_number());
+        assert_eq!(Some(DrawReason::ThreefoldRepetition), move_state.draw_state);
+    }
+
+    #[test]
+    fn test_move_state_fifty_moves() {
+        let king = ChessPiece::new(4, 0, ChessColor::WHITE, PieceType::KING);
+        let rook = ChessPiece::new(0, 0, ChessColor::WHITE, PieceType::ROOK);
+        let enemy_king = ChessPiece::new(4, 7, ChessColor::BLACK, PieceType::KING);
+        let pieces = vec![&king, &rook, &enemy_king];
+        let mut move_state = MoveState {
+            halfmove_clock: 99,
+            ..Default::default()
+        };
+
+        move_state.update_halfmove_clock(&rook, false);
+        move_state.update_draw_state(&ChessColor::BLACK, &pieces);
+        assert_eq!(Some(DrawReason::FiftyMoves), move_state.draw_state);
+
+        move_state.update_halfmove_clock(&rook, true);
+        assert_eq!(0, move_state.halfmove_clock);
+    }
+
+    #[test]
+    fn test_move_state_undo_redo() {
+        let start_fen = "r3k3/1P6/8/3pP3/8/8/8/R3K2R w KQq d6 0 1";
+        let (mut pieces, mut move_state) = FenParser::parse_fen(start_fen).unwrap();
+        let save_fen = |pieces: &Vec<ChessPiece>, move_state: &MoveState| {
+            let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+            FenParser::save_fen(&piece_refs, move_state)
+        };
+
+        for (from, to, promotion) in [
+            ("e5", "d6", None),
+            ("e8", "f7", None),
+            ("b7", "a8", Some(PieceType::QUEEN)),
+            ("f7", "g6", None),
+            ("e1", "g1", None),
+        ] {
+            let from = Notation::cell_from_string(from).unwrap();
+            let to = Notation::cell_from_string(to).unwrap();
+            assert!(move_state
+                .make_move(&mut pieces, &from, &to, promotion)
+                .is_some());
+            move_state.next_move();
+        }
+        let end_fen = save_fen(&pieces, &move_state);
+        assert_eq!("Q7/8/3P2k1/8/8/8/8/R4RK1 b - - 2 3", end_fen);
+
+        let castling_move = move_state.undo_move(&mut pieces).unwrap();
+        assert_eq!(ChessColor::WHITE, move_state.current_collor);
+        assert!(castling_move.castling_rook.is_some());
+        while move_state.undo_move(&mut pieces).is_some() {}
+        assert_eq!(start_fen, save_fen(&pieces, &move_state));
+        assert_eq!(5, move_state.redo_history.len());
+        assert!(move_state.redo_history[4].is_en_passant);
+
+        while move_state.redo_move(&mut pieces).is_some() {}
+        assert_eq!(end_fen, save_fen(&pieces, &move_state));
+        assert_eq!(5, move_state.move_history.len());
+        assert_eq!("O-O", move_state.move_history[4].san);
+    }
+
+    #[test]
+    fn test_move_state_last_move_and_checked_king_cells() {
+        let (mut pieces, mut move_state) =
+            FenParser::parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+                .unwrap();
+        let cell = |name: &str| Notation::cell_from_string(name).unwrap();
+        assert_eq!(None, move_state.last_move_cells());
+
+        for (from, to) in [("e2", "e4"), ("f7", "f6"), ("d1", "h5")] {
+            move_state
+                .make_move(&mut pieces, &cell(from), &cell(to), None)
+                .unwrap();
+            move_state.next_move();
+        }
+        let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+        assert_eq!(Some((cell("d1"), cell("h5"))), move_state.last_move_cells());
+        assert_eq!(Some(cell("e8")), move_state.checked_king_cell(&piece_refs));
+
+        move_state.undo_move(&mut pieces);
+        let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+        assert_eq!(Some((cell("f7"), cell("f6"))), move_state.last_move_cells());
+        assert_eq!(None, move_state.checked_king_cell(&piece_refs));
+
+        // the mated king stays marked
+        for (from, to) in [("f1", "c4"), ("g7", "g5"), ("d1", "h5")] {
+            move_state
+                .make_move(&mut pieces, &cell(from), &cell(to), None)
+                .unwrap();
+            move_state.next_move();
+        }
+        let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+        assert!(move_state.mate_state.is_some());
+        assert_eq!(Some(cell("e8")), move_state.checked_king_cell(&piece_refs));
+    }
+
+    #[test]
+    fn test_move_state_start_ply_while_move_in_progress() {
+        let (mut pieces, mut move_state) =
+            FenParser::parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+                .unwrap();
+        let cell = |name: &str| Notation::cell_from_string(name).unwrap();
+        assert_eq!(0, move_state.start_ply());
+
+        // the move is in the history before the turn passes
+        move_state
+            .make_move(&mut pieces, &cell("e2"), &cell("e4"), None)
+            .unwrap();
+        assert_eq!(0, move_state.ply_count);
+        assert_eq!(1, move_state.move_history.len());
+        assert_eq!(0, move_state.start_ply());
+        move_state.next_move();
+        assert_eq!(0, move_state.start_ply());
+
+        let (mut pieces, mut move_state) =
+            FenParser::parse_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 12").unwrap();
+        assert_eq!(23, move_state.start_ply());
+        move_state
+            .make_move(&mut pieces, &cell("e8"), &cell("d7"), None)
+            .unwrap();
+        assert_eq!(23, move_state.start_ply());
+        move_state.next_move();
+        move_state
+            .make_move(&mut pieces, &cell("e2"), &cell("e4"), None)
+            .unwrap();
+        assert_eq!(23, move_state.start_ply());
+    }
+}
@@ -0,0 +1,39 @@
+use std::ops::Range;
+
+#[derive(Clone, PartialEq, Eq, Hash, Default, Debug)]
+pub enum ChessColor {
+    #[default]
+    WHITE,
+    BLACK,
+}
+
+impl ChessColor {
+    pub fn opposite(&self) -> Self {
+        match self {
+            ChessColor::WHITE => ChessColor::BLACK,
+            ChessColor::BLACK => ChessColor::WHITE,
+        }
+    }
+}
+
+// first and last rank or file of the 8x8 board
+pub const FIRST_ELEMENT: i8 = 0;
+pub const LAST_ELEMENT: i8 = 7;
+
+// position of a cell on board
+#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub struct CellPosition {
+    pub i: i8,
+    pub j: i8,
+}
+
+impl CellPosition {
+    // ranks or files of the board
+    pub fn cell_range() -> Range<i8> {
+        FIRST_ELEMENT..LAST_ELEMENT + 1
+    }
+
+    pub fn is_on_board(&self) -> bool {
+        CellPosition::cell_range().contains(&self.i) && CellPosition::cell_range().contains(&self.j)
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt;
+
+use crate::{
+    castling_rights::CastlingRights,
+    chess_move_state::MoveState,
+    chess_piece::{ChessPiece, PieceType},
+    common_chess::{CellPosition, ChessColor},
+    notation::Notation,
+};
+
+pub const START_POSITION_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FenParseError {
+    WrongFieldCount(usize),
+    WrongRankCount(usize),
+    WrongRankLength(usize),
+    UnknownPiece(char),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassant(String),
+    InvalidNumber(String),
+}
+
+impl fmt::Display for FenParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenParseError::WrongFieldCount(count) => {
+                write!(f, "expected 4 to 6 fields, found {}", count)
+            }
+            FenParseError::WrongRankCount(count) => write!(f, "expected 8 ranks, found {}", count),
+            FenParseError::WrongRankLength(rank) => {
+                write!(f, "rank {} does not describe 8 cells", rank)
+            }
+            FenParseError::UnknownPiece(symbol) => write!(f, "unknown piece symbol '{}'", symbol),
+            FenParseError::InvalidSideToMove(field) => {
+                write!(f, "invalid side to move '{}'", field)
+            }
+            FenParseError::InvalidCastling(field) => write!(f, "invalid castling '{}'", field),
+            FenParseError::InvalidEnPassant(field) => write!(f, "invalid en passant '{}'", field),
+            FenParseError::InvalidNumber(field) => write!(f, "invalid move counter '{}'", field),
+        }
+    }
+}
+
+impl std::error::Error for FenParseError {}
+
+pub struct FenParser;
+
+impl FenParser {
+    // pieces plus the side to move, castling, en passant and move counters
+    pub fn parse_fen(fen: &str) -> Result<(Vec<ChessPiece>, MoveState), FenParseError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(FenParseError::WrongFieldCount(fields.len()));
+        }
+
+        let pieces = FenParser::parse_placement(fields[0])?;
+        let current_collor = match fields[1] {
+            "w" => ChessColor::WHITE,
+            "b" => ChessColor::BLACK,
+            other => return Err(FenParseError::InvalidSideToMove(other.to_string())),
+        };
+        let castling_rights = FenParser::parse_castling(fields[2])?;
+        let en_passant_target = match fields[3] {
+            "-" => None,
+            cell => Some(
+                Notation::cell_from_string(cell)
+                    .filter(|pos| pos.j == 2 || pos.j == 5)
+                    .ok_or_else(|| FenParseError::InvalidEnPassant(cell.to_string()))?,
+            ),
+        };
+        let halfmove_clock = FenParser::parse_number(fields.get(4).copied().unwrap_or("0"))?;
+        let fullmove_number = FenParser::parse_number(fields.get(5).copied().unwrap_or("1"))?;
+        let ply_count = fullmove_number.max(1) * 2 - 2
+            + match current_collor {
+                ChessColor::WHITE => 0,
+                ChessColor::BLACK => 1,
+            };
+
+        let move_state = MoveState {
+            current_collor,
+            castling_rights,
+            en_passant_target,
+            halfmove_clock,
+            ply_count,
+            ..Default::default()
+        };
+        Ok((pieces, move_state))
+    }
+
+    fn parse_placement(placement: &str) -> Result<Vec<ChessPiece>, FenParseError> {
+        let ranks: Vec<&str> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(FenParseError::WrongRankCount(ranks.len()));
+        }
+        let mut pieces = Vec::new();
+        // the first rank in FEN is the 8th one
+        for (rank_index, rank) in ranks.iter().enumerate() {
+            let j = 7 - rank_index as i8;
+            let mut i: i8 = 0;
+            for symbol in rank.chars() {
+                if let Some(empty_cells) = symbol.to_digit(10) {
+                    i += empty_cells as i8;
+                    continue;
+                }
+                if i > 7 {
+                    return Err(FenParseError::WrongRankLength(j as usize + 1));
+                }
+                let (color, piece_type) = FenParser::parse_piece_symbol(symbol)?;
+                pieces.push(ChessPiece::new(i, j, color, piece_type));
+                i += 1;
+            }
+            if i != 8 {
+                return Err(FenParseError::WrongRankLength(j as usize + 1));
+            }
+        }
+        Ok(pieces)
+    }
+
+    fn parse_piece_symbol(symbol: char) -> Result<(ChessColor, PieceType), FenParseError> {
+        let color = if symbol.is_ascii_uppercase() {
+            ChessColor::WHITE
+        } else {
+            ChessColor::BLACK
+        };
+        let piece_type = match symbol.to_ascii_lowercase() {
+            'p' => PieceType::PAWN,
+            'n' => PieceType::KNIGHT,
+            'b' => PieceType::BISHOP,
+            'r' => PieceType::ROOK,
+            'q' => PieceType::QUEEN,
+            'k' => PieceType::KING,
+            _ => return Err(FenParseError::UnknownPiece(symbol)),
+        };
+        Ok((color, piece_type))
+    }
+
+    fn parse_castling(field: &str) -> Result<CastlingRights, FenParseError> {
+        let mut castling_rights = CastlingRights::none();
+        if field == "-" {
+            return Ok(castling_rights);
+        }
+        for symbol in field.chars() {
+            match symbol {
+                'K' => castling_rights.white_king_side = true,
+                'Q' => castling_rights.white_queen_side = true,
+                'k' => castling_rights.black_king_side = true,
+                'q' => castling_rights.black_queen_side = true,
+                _ => return Err(FenParseError::InvalidCastling(field.to_string())),
+            }
+        }
+        Ok(castling_rights)
+    }
+
+    fn parse_number(field: &str) -> Result<u32, FenParseError> {
+        field
+            .parse()
+            .map_err(|_| FenParseError::InvalidNumber(field.to_string()))
+    }
+
+    pub fn piece_symbol(piece: &ChessPiece) -> char {
+        let symbol = match piece.piece_type {
+            PieceType::PAWN => 'p',
+            PieceType::KNIGHT => 'n',
+            PieceType::BISHOP => 'b',
+            PieceType::ROOK => 'r',
+            PieceType::QUEEN => 'q',
+            PieceType::KING => 'k',
+        };
+        match piece.color {
+            ChessColor::WHITE => symbol.to_ascii_uppercase(),
+            ChessColor::BLACK => symbol,
+        }
+    }
+
+    pub fn save_fen(pieces: &Vec<&ChessPiece>, move_state: &MoveState) -> String {
+        let mut ranks = Vec::new();
+        for j in CellPosition::cell_range().rev() {
+            let mut rank = String::new();
+            let mut empty_cells = 0;
+            for i in CellPosition::cell_range() {
+                match pieces.iter().find(|cp| cp.pos == CellPosition { i, j }) {
+                    Some(piece) => {
+                        if empty_cells > 0 {
+                            rank.push_str(&empty_cells.to_string());
+                            empty_cells = 0;
+                        }
+                        rank.push(FenParser::piece_symbol(piece));
+                    }
+                    None => empty_cells += 1,
+                }
+            }
+            if empty_cells > 0 {
+                rank.push_str(&empty_cells.to_string());
+            }
+            ranks.push(rank);
+        }
+
+        let side_to_move = match move_state.current_collor {
+            ChessColor::WHITE => "w",
+            ChessColor::BLACK => "b",
+        };
+        let castling_rights = &move_state.castling_rights;
+        let mut castling: String = [
+            (castling_rights.white_king_side, 'K'),
+            (castling_rights.white_queen_side, 'Q'),
+            (castling_rights.black_king_side, 'k'),
+            (castling_rights.black_queen_side, 'q'),
+        ]
+        .iter()
+        .filter(|(is_allowed, _)| *is_allowed)
+        .map(|(_, symbol)| *symbol)
+        .collect();
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        let en_passant = move_state
+            .en_passant_target
+            .map_or("-".to_string(), |pos| Notation::cell_to_string(&pos));
+
+        format!(
+            "{} {} {} {} {} {}",
+            ranks.join("/"),
+            side_to_move,
+            castling,
+            en_passant,
+            move_state.halfmove_clock,
+            move_state.fullmove_number()
+        )
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_start_position() {
+        let (pieces, move_state) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        assert_eq!(32, pieces.len());
+        assert_eq!(ChessColor::WHITE, move_state.current_collor);
+        assert_eq!(CastlingRights::default(), move_state.castling_rights);
+        assert_eq!(0, move_state.ply_count);
+
+        let king = pieces
+            .iter()
+            .find(|cp| cp.piece_type == PieceType::KING && cp.color == ChessColor::WHITE)
+            .unwrap();
+        assert_eq!(CellPosition { i: 4, j: 0 }, king.pos);
+    }
+
+    #[test]
+    fn test_fen_round_trip() {
+        for fen in [
+            START_POSITION_FEN,
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Kq - 3 17",
+            "8/8/8/8/8/8/8/K6k w - - 99 80",
+        ] {
+            let (pieces, move_state) = FenParser::parse_fen(fen).unwrap();
+            let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+            assert_eq!(fen, FenParser::save_fen(&pieces, &move_state));
+        }
+    }
+
+    #[test]
+    fn test_parse_malformed_fen() {
+        assert_eq!(
+            Err(FenParseError::WrongFieldCount(1)),
+            FenParser::parse_fen("8/8/8/8/8/8/8/8").map(|_| ())
+        );
+        assert_eq!(
+            Err(FenParseError::WrongRankCount(7)),
+            FenParser::parse_fen("8/8/8/8/8/8/8 w - -").map(|_| ())
+        );
+        assert_eq!(
+            Err(FenParseError::WrongRankLength(8)),
+            FenParser::parse_fen("9/8/8/8/8/8/8/8 w - -").map(|_| ())
+        );
+        assert_eq!(
+            Err(FenParseError::UnknownPiece('x')),
+            FenParser::parse_fen("x7/8/8/8/8/8/8/8 w - -").map(|_| ())
+        );
+        assert_eq!(
+            Err(FenParseError::InvalidSideToMove("x".to_string())),
+            FenParser::parse_fen("8/8/8/8/8/8/8/8 x - -").map(|_| ())
+        );
+        assert_eq!(
+            Err(FenParseError::InvalidCastling("KX".to_string())),
+            FenParser::parse_fen("8/8/8/8/8/8/8/8 w KX -").map(|_| ())
+        );
+        assert_eq!(
+            Err(FenParseError::InvalidEnPassant("e4".to_string())),
+            FenParser::parse_fen("8/8/8/8/8/8/8/8 w - e4").map(|_| ())
+        );
+        assert_eq!(
+            Err(FenParseError::InvalidNumber("-1".to_string())),
+            FenParser::parse_fen("8/8/8/8/8/8/8/8 w - - -1 1").map(|_| ())
+        );
+    }
+}
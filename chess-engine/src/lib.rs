@@ -0,0 +1,19 @@
+// chess rules without any rendering: pieces and positions, move generation,
+// game status, FEN/PGN and SAN/LAN/UCI move notation, search, perft, the UCI protocol on both ends
+// and the network protocol between two players
+pub mod ai_engine;
+pub mod bitboard;
+pub mod castling_rights;
+pub mod chess_move;
+pub mod chess_move_state;
+pub mod chess_piece;
+pub mod common_chess;
+pub mod fen_parser;
+pub mod net_peer;
+pub mod net_protocol;
+pub mod notation;
+pub mod perft;
+pub mod pgn_parser;
+pub mod piece_parser;
+pub mod uci;
+pub mod uci_client;
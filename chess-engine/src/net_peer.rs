@@ -0,0 +1,412 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::AtomicBool,
+        mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    ai_engine::AiEngine,
+    chess_move_state::{DrawReason, MoveState},
+    chess_piece::ChessPiece,
+    common_chess::ChessColor,
+    fen_parser::{FenParser, START_POSITION_FEN},
+    net_protocol::{NetMessage, NetProtocol, NetProtocolError, PROTOCOL_VERSION},
+};
+
+pub const DEFAULT_PORT: u16 = 7777;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// TCP connection to the other player, incoming lines are parsed on a reader thread
+// so that the game polls them without blocking
+pub struct NetPeer {
+    stream: TcpStream,
+    receiver: Mutex<Receiver<Result<NetMessage, NetProtocolError>>>,
+}
+
+impl NetPeer {
+    // listener of the host, it does not block while nobody joins
+    pub fn listen(port: u16) -> io::Result<TcpListener> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(listener)
+    }
+
+    // the peer that joined the host, None while nobody did
+    pub fn try_accept(listener: &TcpListener) -> io::Result<Option<NetPeer>> {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                NetPeer::new(stream).map(Some)
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn join(address: &str) -> io::Result<NetPeer> {
+        let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "address is not resolved")
+        })?;
+        NetPeer::new(TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?)
+    }
+
+    fn new(stream: TcpStream) -> io::Result<NetPeer> {
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                if sender.send(NetMessage::parse(&line)).is_err() {
+                    return;
+                }
+            }
+            let _ = sender.send(Err(NetProtocolError::Disconnected));
+        });
+        Ok(NetPeer {
+            stream,
+            receiver: Mutex::new(receiver),
+        })
+    }
+
+    pub fn send(&self, message: &NetMessage) -> io::Result<()> {
+        let mut stream = &self.stream;
+        writeln!(stream, "{}", message.to_line())?;
+        stream.flush()
+    }
+
+    // next message if one has come
+    pub fn try_receive(&self) -> Option<Result<NetMessage, NetProtocolError>> {
+        let receiver = self.receiver.lock().unwrap_or_else(|err| err.into_inner());
+        match receiver.try_recv() {
+            Ok(message) => Some(message),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(NetProtocolError::Disconnected)),
+        }
+    }
+
+    // next message, None once the time is over
+    pub fn receive_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Option<Result<NetMessage, NetProtocolError>> {
+        let receiver = self.receiver.lock().unwrap_or_else(|err| err.into_inner());
+        match receiver.recv_timeout(timeout) {
+            Ok(message) => Some(message),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err(NetProtocolError::Disconnected)),
+        }
+    }
+}
+
+impl Drop for NetPeer {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+// other side of a network game without a window, the engine finds its moves;
+// it accepts every draw offer
+pub struct HeadlessPeer {
+    pub color: ChessColor,
+    pub max_depth: u32,
+    pub think_time: Duration,
+    // the game is given up once the other side stays silent for longer
+    pub receive_timeout: Duration,
+}
+
+impl HeadlessPeer {
+    // `peer host [<port>]` plays white, `peer join [<address>]` the color the host left
+    pub fn run_command(args: &[String]) -> Result<(), String> {
+        let usage = "usage: peer host [<port>] | peer join [<address>]";
+        let peer = HeadlessPeer {
+            color: ChessColor::WHITE,
+            max_depth: 64,
+            think_time: Duration::from_secs(1),
+            receive_timeout: Duration::from_secs(10 * 60),
+        };
+        let result = match args.first().map(String::as_str) {
+            Some("host") => {
+                let port = match args.get(1) {
+                    Some(port) => port.parse().map_err(|_| usage.to_string())?,
+                    None => DEFAULT_PORT,
+                };
+                let listener =
+                    TcpListener::bind(("0.0.0.0", port)).map_err(|err| err.to_string())?;
+                println!("waiting for a peer on port {}", port);
+                let (stream, _) = listener.accept().map_err(|err| err.to_string())?;
+                let net_peer = NetPeer::new(stream).map_err(|err| err.to_string())?;
+                peer.play_host(&net_peer)
+            }
+            Some("join") => {
+                let default_address = format!("127.0.0.1:{}", DEFAULT_PORT);
+                let address = args.get(1).unwrap_or(&default_address);
+                let net_peer = NetPeer::join(address).map_err(|err| err.to_string())?;
+                peer.play_join(&net_peer)
+            }
+            _ => return Err(usage.to_string()),
+        };
+        let move_state = result.map_err(|err| err.to_string())?;
+        println!("game over {}", move_state.result());
+        Ok(())
+    }
+
+    // says hello and sends the start position, then plays its color
+    pub fn play_host(&self, net_peer: &NetPeer) -> Result<MoveState, NetProtocolError> {
+        let (pieces, move_state) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        HeadlessPeer::send(net_peer, &self.hello(&self.color))?;
+        HeadlessPeer::send(net_peer, &NetProtocol::sync_message(&pieces, &move_state))?;
+        NetProtocol::check_hello(&self.receive(net_peer)?, Some(&self.color))?;
+        self.play(net_peer, &self.color, pieces, move_state)
+    }
+
+    // takes the color the host left and its position
+    pub fn play_join(&self, net_peer: &NetPeer) -> Result<MoveState, NetProtocolError> {
+        let host_color = NetProtocol::check_hello(&self.receive(net_peer)?, None)?;
+        let color = host_color.opposite();
+        HeadlessPeer::send(net_peer, &self.hello(&color))?;
+        let (pieces, move_state) = NetProtocol::apply_sync(&self.receive(net_peer)?)?;
+        self.play(net_peer, &color, pieces, move_state)
+    }
+
+    fn play(
+        &self,
+        net_peer: &NetPeer,
+        color: &ChessColor,
+        mut pieces: Vec<ChessPiece>,
+        mut move_state: MoveState,
+    ) -> Result<MoveState, NetProtocolError> {
+        let is_cancelled = AtomicBool::new(false);
+        while !move_state.is_game_over() {
+            if move_state.current_collor == *color {
+                let Some(engine_move) = AiEngine::best_move(
+                    &pieces,
+                    &move_state,
+                    self.max_depth,
+                    self.think_time,
+                    &is_cancelled,
+                ) else {
+                    break;
+                };
+                let index = move_state.move_history.len();
+                move_state.make_move(
+                    &mut pieces,
+                    &engine_move.from,
+                    &engine_move.to,
+                    engine_move.promotion,
+                );
+                move_state.next_move();
+                if let Some(message) = NetProtocol::move_message(&move_state, index) {
+                    HeadlessPeer::send(net_peer, &message)?;
+                }
+                continue;
+            }
+
+            match self.receive(net_peer)? {
+                message @ NetMessage::Move { .. } => {
+                    let remote_color = color.opposite();
+                    NetProtocol::apply_move(&mut pieces, &mut move_state, &remote_color, &message)?;
+                    move_state.next_move();
+                }
+                NetMessage::Resign => move_state.resign_state = Some(color.opposite()),
+                NetMessage::DrawOffer => {
+                    HeadlessPeer::send(net_peer, &NetMessage::DrawOffer)?;
+                    move_state.draw_state = Some(DrawReason::Agreement);
+                }
+                NetMessage::Chat(text) => println!("chat: {}", text),
+                message => {
+                    return Err(NetProtocolError::UnexpectedMessage(message.to_line()));
+                }
+            }
+        }
+        Ok(move_state)
+    }
+
+    fn hello(&self, color: &ChessColor) -> NetMessage {
+        NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            color: color.clone(),
+        }
+    }
+
+    fn send(net_peer: &NetPeer, message: &NetMessage) -> Result<(), NetProtocolError> {
+        net_peer
+            .send(message)
+            .map_err(|_| NetProtocolError::Disconnected)
+    }
+
+    fn receive(&self, net_peer: &NetPeer) -> Result<NetMessage, NetProtocolError> {
+        net_peer
+            .receive_timeout(self.receive_timeout)
+            .unwrap_or(Err(NetProtocolError::Disconnected))
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use std::thread::JoinHandle;
+
+    use super::*;
+    use crate::notation::Notation;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    // headless host of white on a loopback port and the peer joined to it
+    fn host_game(
+        receive_timeout: Duration,
+    ) -> (JoinHandle<Result<MoveState, NetProtocolError>>, NetPeer) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let host = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let net_peer = NetPeer::new(stream).unwrap();
+            HeadlessPeer {
+                color: ChessColor::WHITE,
+                max_depth: 1,
+                think_time: Duration::from_secs(1),
+                receive_timeout,
+            }
+            .play_host(&net_peer)
+        });
+        (host, NetPeer::join(&address).unwrap())
+    }
+
+    fn receive(net_peer: &NetPeer) -> NetMessage {
+        net_peer.receive_timeout(TIMEOUT).unwrap().unwrap()
+    }
+
+    fn move_message(index: usize, uci: &str) -> NetMessage {
+        let (from, to, promotion) = Notation::move_from_uci(uci).unwrap();
+        NetMessage::Move {
+            index,
+            from,
+            to,
+            promotion,
+        }
+    }
+
+    #[test]
+    fn test_net_peer_loopback_game() {
+        let (host, net_peer) = host_game(TIMEOUT);
+        assert_eq!(
+            Ok(ChessColor::WHITE),
+            NetProtocol::check_hello(&receive(&net_peer), Some(&ChessColor::BLACK))
+        );
+        net_peer
+            .send(&NetMessage::Hello {
+                version: PROTOCOL_VERSION,
+                color: ChessColor::BLACK,
+            })
+            .unwrap();
+        let (mut pieces, mut move_state) = NetProtocol::apply_sync(&receive(&net_peer)).unwrap();
+
+        // the host's moves are legal for the joined side as well
+        for _ in 0..2 {
+            let message = receive(&net_peer);
+            NetProtocol::apply_move(&mut pieces, &mut move_state, &ChessColor::WHITE, &message)
+                .unwrap();
+            move_state.next_move();
+            let index = move_state.move_history.len();
+            let black_move =
+                AiEngine::best_move(&pieces, &move_state, 1, TIMEOUT, &AtomicBool::new(false))
+                    .unwrap();
+            move_state.make_move(&mut pieces, &black_move.from, &black_move.to, None);
+            move_state.next_move();
+            net_peer
+                .send(&NetProtocol::move_message(&move_state, index).unwrap())
+                .unwrap();
+        }
+        assert!(matches!(
+            receive(&net_peer),
+            NetMessage::Move { index: 4, .. }
+        ));
+
+        net_peer.send(&NetMessage::Chat("gg".to_string())).unwrap();
+        net_peer.send(&NetMessage::Resign).unwrap();
+        let move_state = host.join().unwrap().unwrap();
+        assert_eq!("1-0", move_state.result());
+        assert_eq!(5, move_state.move_history.len());
+    }
+
+    #[test]
+    fn test_net_peer_draw_offer() {
+        let (host, net_peer) = host_game(TIMEOUT);
+        receive(&net_peer);
+        net_peer
+            .send(&NetMessage::Hello {
+                version: PROTOCOL_VERSION,
+                color: ChessColor::BLACK,
+            })
+            .unwrap();
+        receive(&net_peer);
+        receive(&net_peer);
+        net_peer.send(&NetMessage::DrawOffer).unwrap();
+        assert_eq!(NetMessage::DrawOffer, receive(&net_peer));
+        let move_state = host.join().unwrap().unwrap();
+        assert_eq!(Some(DrawReason::Agreement), move_state.draw_state);
+        assert_eq!("1/2-1/2", move_state.result());
+    }
+
+    #[test]
+    fn test_net_peer_rejects_illegal_move() {
+        let (host, net_peer) = host_game(TIMEOUT);
+        receive(&net_peer);
+        net_peer
+            .send(&NetMessage::Hello {
+                version: PROTOCOL_VERSION,
+                color: ChessColor::BLACK,
+            })
+            .unwrap();
+        receive(&net_peer);
+        receive(&net_peer);
+        net_peer.send(&move_message(1, "e7e4")).unwrap();
+        assert_eq!(
+            Err(NetProtocolError::IllegalMove {
+                index: 1,
+                uci: "e7e4".to_string()
+            }),
+            host.join().unwrap().map(|_| ())
+        );
+        assert_eq!(
+            Some(Err(NetProtocolError::Disconnected)),
+            net_peer.receive_timeout(TIMEOUT)
+        );
+    }
+
+    #[test]
+    fn test_net_peer_rejects_same_color() {
+        let (host, net_peer) = host_game(TIMEOUT);
+        receive(&net_peer);
+        net_peer
+            .send(&NetMessage::Hello {
+                version: PROTOCOL_VERSION,
+                color: ChessColor::WHITE,
+            })
+            .unwrap();
+        assert_eq!(
+            Err(NetProtocolError::WrongColor),
+            host.join().unwrap().map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_net_peer_silent_peer() {
+        let (host, net_peer) = host_game(Duration::from_millis(200));
+        receive(&net_peer);
+        assert_eq!(
+            Err(NetProtocolError::Disconnected),
+            host.join().unwrap().map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,530 @@
+use std::fmt;
+
+use crate::{
+    chess_move::ChessMove,
+    chess_move_state::MoveState,
+    chess_piece::{ChessPiece, PieceType},
+    common_chess::{CellPosition, ChessColor},
+    fen_parser::FenParser,
+    notation::Notation,
+};
+
+// bumped whenever a message changes, peers of other versions are refused
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NetProtocolError {
+    UnknownMessage(String),
+    InvalidMessage(String),
+    VersionMismatch(u32),
+    // the peer has to play the other color
+    WrongColor,
+    IllegalMove { index: usize, uci: String },
+    // a message that does not fit the state of the game, e.g. a move before hello
+    UnexpectedMessage(String),
+    Disconnected,
+}
+
+impl fmt::Display for NetProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetProtocolError::UnknownMessage(line) => write!(f, "unknown message '{}'", line),
+            NetProtocolError::InvalidMessage(line) => write!(f, "invalid message '{}'", line),
+            NetProtocolError::VersionMismatch(version) => write!(
+                f,
+                "peer speaks protocol version {}, expected {}",
+                version, PROTOCOL_VERSION
+            ),
+            NetProtocolError::WrongColor => write!(f, "peer plays the same color"),
+            NetProtocolError::IllegalMove { index, uci } => {
+                write!(f, "illegal move {} at ply {}", uci, index + 1)
+            }
+            NetProtocolError::UnexpectedMessage(line) => {
+                write!(f, "unexpected message '{}'", line)
+            }
+            NetProtocolError::Disconnected => write!(f, "peer disconnected"),
+        }
+    }
+}
+
+impl std::error::Error for NetProtocolError {}
+
+// one line of text on the wire for each message:
+//   hello <version> <w|b>
+//   move <index> <uci move>
+//   resign
+//   draw
+//   chat <text>
+//   sync fen <fen> moves [<uci move> ...]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NetMessage {
+    // first message of both sides with the color the sender plays
+    Hello {
+        version: u32,
+        color: ChessColor,
+    },
+    // index of the move in the game history, both sides know the moves before it
+    Move {
+        index: usize,
+        from: CellPosition,
+        to: CellPosition,
+        promotion: Option<PieceType>,
+    },
+    Resign,
+    // an offer answered with an offer is a draw by agreement
+    DrawOffer,
+    Chat(String),
+    // start position and moves of the game, the host sends it after hello
+    SyncState {
+        fen: String,
+        moves: Vec<String>,
+    },
+}
+
+impl NetMessage {
+    pub fn to_line(&self) -> String {
+        match self {
+            NetMessage::Hello { version, color } => {
+                format!("hello {} {}", version, NetMessage::color_letter(color))
+            }
+            NetMessage::Move {
+                index,
+                from,
+                to,
+                promotion,
+            } => format!(
+                "move {} {}",
+                index,
+                Notation::move_to_uci(from, to, promotion)
+            ),
+            NetMessage::Resign => "resign".to_string(),
+            NetMessage::DrawOffer => "draw".to_string(),
+            // a line break would start another message
+            NetMessage::Chat(text) => format!("chat {}", text.replace(['\r', '\n'], " ")),
+            NetMessage::SyncState { fen, moves } => {
+                let mut line = format!("sync fen {} moves", fen);
+                for uci_move in moves {
+                    line.push(' ');
+                    line.push_str(uci_move);
+                }
+                line
+            }
+        }
+    }
+
+    pub fn parse(line: &str) -> Result<NetMessage, NetProtocolError> {
+        let invalid = || NetProtocolError::InvalidMessage(line.to_string());
+        let line = line.trim_end_matches(['\r', '\n']);
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let tokens: Vec<&str> = args.split_whitespace().collect();
+        match command {
+            "hello" => {
+                let [version, color] = tokens[..] else {
+                    return Err(invalid());
+                };
+                Ok(NetMessage::Hello {
+                    version: version.parse().map_err(|_| invalid())?,
+                    color: NetMessage::parse_color(color).ok_or_else(invalid)?,
+                })
+            }
+            "move" => {
+                let [index, uci_move] = tokens[..] else {
+                    return Err(invalid());
+                };
+                let (from, to, promotion) =
+                    Notation::move_from_uci(uci_move).ok_or_else(invalid)?;
+                Ok(NetMessage::Move {
+                    index: index.parse().map_err(|_| invalid())?,
+                    from,
+                    to,
+                    promotion,
+                })
+            }
+            "resign" if tokens.is_empty() => Ok(NetMessage::Resign),
+            "draw" if tokens.is_empty() => Ok(NetMessage::DrawOffer),
+            "chat" => Ok(NetMessage::Chat(args.to_string())),
+            "sync" => {
+                let moves_index = tokens
+                    .iter()
+                    .position(|token| *token == "moves")
+                    .ok_or_else(invalid)?;
+                if tokens.first() != Some(&"fen") || moves_index < 2 {
+                    return Err(invalid());
+                }
+                Ok(NetMessage::SyncState {
+                    fen: tokens[1..moves_index].join(" "),
+                    moves: tokens[moves_index + 1..]
+                        .iter()
+                        .map(|uci_move| uci_move.to_string())
+                        .collect(),
+                })
+            }
+            "resign" | "draw" => Err(invalid()),
+            _ => Err(NetProtocolError::UnknownMessage(line.to_string())),
+        }
+    }
+
+    fn color_letter(color: &ChessColor) -> &'static str {
+        match color {
+            ChessColor::WHITE => "w",
+            ChessColor::BLACK => "b",
+        }
+    }
+
+    fn parse_color(letter: &str) -> Option<ChessColor> {
+        match letter {
+            "w" => Some(ChessColor::WHITE),
+            "b" => Some(ChessColor::BLACK),
+            _ => None,
+        }
+    }
+}
+
+// rules checks of the messages, both peers check every move the other one sends
+pub struct NetProtocol;
+
+impl NetProtocol {
+    // color of the peer from its hello
+    pub fn check_hello(
+        message: &NetMessage,
+        local_color: Option<&ChessColor>,
+    ) -> Result<ChessColor, NetProtocolError> {
+        let NetMessage::Hello { version, color } = message else {
+            return Err(NetProtocolError::UnexpectedMessage(message.to_line()));
+        };
+        if *version != PROTOCOL_VERSION {
+            return Err(NetProtocolError::VersionMismatch(*version));
+        }
+        if local_color == Some(color) {
+            return Err(NetProtocolError::WrongColor);
+        }
+        Ok(color.clone())
+    }
+
+    // message of the move at the index of the history
+    pub fn move_message(move_state: &MoveState, index: usize) -> Option<NetMessage> {
+        let chess_move = move_state.move_history.get(index)?;
+        Some(NetMessage::Move {
+            index,
+            from: chess_move.piece.pos,
+            to: chess_move.to,
+            promotion: chess_move.promotion.clone(),
+        })
+    }
+
+    // the move has to be the peer's next one in turn, the turn passes with next_move
+    pub fn check_move_turn(
+        move_state: &MoveState,
+        remote_color: &ChessColor,
+        index: usize,
+        uci: &str,
+    ) -> Result<(), NetProtocolError> {
+        if index != move_state.move_history.len()
+            || move_state.current_collor != *remote_color
+            || move_state.promotion_cell.is_some()
+            || move_state.is_game_over()
+        {
+            return Err(NetProtocolError::IllegalMove {
+                index,
+                uci: uci.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    // makes a move of the peer after checking it against the rules
+    pub fn apply_move(
+        pieces: &mut Vec<ChessPiece>,
+        move_state: &mut MoveState,
+        remote_color: &ChessColor,
+        message: &NetMessage,
+    ) -> Result<ChessMove, NetProtocolError> {
+        let NetMessage::Move {
+            index,
+            from,
+            to,
+            promotion,
+        } = message
+        else {
+            return Err(NetProtocolError::UnexpectedMessage(message.to_line()));
+        };
+        let uci = Notation::move_to_uci(from, to, promotion);
+        NetProtocol::check_move_turn(move_state, remote_color, *index, &uci)?;
+        // nothing is changed by a rejected move, a promotion needs its piece
+        if !move_state.is_complete_move(pieces, from, to, promotion) {
+            return Err(NetProtocolError::IllegalMove { index: *index, uci });
+        }
+        move_state
+            .make_move(pieces, from, to, promotion.clone())
+            .ok_or(NetProtocolError::IllegalMove { index: *index, uci })
+    }
+
+    // the whole game for a peer joining it
+    pub fn sync_message(pieces: &[ChessPiece], move_state: &MoveState) -> NetMessage {
+        let fen = match &move_state.start_fen {
+            Some(start_fen) => start_fen.clone(),
+            None => {
+                let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+                FenParser::save_fen(&pieces, move_state)
+            }
+        };
+        let moves = move_state
+            .move_history
+            .iter()
+            .map(|chess_move| {
+                Notation::move_to_uci(&chess_move.piece.pos, &chess_move.to, &chess_move.promotion)
+            })
+            .collect();
+        NetMessage::SyncState { fen, moves }
+    }
+
+    // replays the synced game checking every move
+    pub fn apply_sync(
+        message: &NetMessage,
+    ) -> Result<(Vec<ChessPiece>, MoveState), NetProtocolError> {
+        let NetMessage::SyncState { fen, moves } = message else {
+            return Err(NetProtocolError::UnexpectedMessage(message.to_line()));
+        };
+        let (mut pieces, mut move_state) = FenParser::parse_fen(fen)
+            .map_err(|_| NetProtocolError::InvalidMessage(message.to_line()))?;
+        for (index, uci_move) in moves.iter().enumerate() {
+            let illegal_move = || NetProtocolError::IllegalMove {
+                index,
+                uci: uci_move.clone(),
+            };
+            let (from, to, promotion) =
+                Notation::move_from_uci(uci_move).ok_or_else(illegal_move)?;
+            let remote_color = move_state.current_collor.clone();
+            NetProtocol::apply_move(
+                &mut pieces,
+                &mut move_state,
+                &remote_color,
+                &NetMessage::Move {
+                    index,
+                    from,
+                    to,
+                    promotion,
+                },
+            )?;
+            move_state.next_move();
+        }
+        Ok((pieces, move_state))
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use super::*;
+    use crate::fen_parser::START_POSITION_FEN;
+
+    #[test]
+    fn test_net_message_round_trip() {
+        let messages = [
+            NetMessage::Hello {
+                version: PROTOCOL_VERSION,
+                color: ChessColor::BLACK,
+            },
+            NetMessage::Move {
+                index: 12,
+                from: Notation::cell_from_string("a7").unwrap(),
+                to: Notation::cell_from_string("a8").unwrap(),
+                promotion: Some(PieceType::KNIGHT),
+            },
+            NetMessage::Resign,
+            NetMessage::DrawOffer,
+            NetMessage::Chat("good luck, have fun".to_string()),
+            NetMessage::SyncState {
+                fen: START_POSITION_FEN.to_string(),
+                moves: vec!["e2e4".to_string(), "c7c5".to_string()],
+            },
+            NetMessage::SyncState {
+                fen: START_POSITION_FEN.to_string(),
+                moves: vec![],
+            },
+        ];
+        for message in messages {
+            assert_eq!(Ok(message.clone()), NetMessage::parse(&message.to_line()));
+        }
+        assert_eq!(
+            "move 12 a7a8n",
+            NetMessage::Move {
+                index: 12,
+                from: Notation::cell_from_string("a7").unwrap(),
+                to: Notation::cell_from_string("a8").unwrap(),
+                promotion: Some(PieceType::KNIGHT),
+            }
+            .to_line()
+        );
+        assert_eq!(
+            "chat two lines",
+            NetMessage::Chat("two\nlines".to_string()).to_line()
+        );
+    }
+
+    #[test]
+    fn test_net_message_invalid() {
+        assert_eq!(
+            Err(NetProtocolError::UnknownMessage("takeback".to_string())),
+            NetMessage::parse("takeback")
+        );
+        for line in [
+            "hello 1",
+            "hello x w",
+            "move 1 e2",
+            "resign now",
+            "sync moves e2e4",
+        ] {
+            assert_eq!(
+                Err(NetProtocolError::InvalidMessage(line.to_string())),
+                NetMessage::parse(line)
+            );
+        }
+    }
+
+    #[test]
+    fn test_net_protocol_hello() {
+        let hello = NetMessage::Hello {
+            version: PROTOCOL_VERSION,
+            color: ChessColor::WHITE,
+        };
+        assert_eq!(
+            Ok(ChessColor::WHITE),
+            NetProtocol::check_hello(&hello, None)
+        );
+        assert_eq!(
+            Err(NetProtocolError::WrongColor),
+            NetProtocol::check_hello(&hello, Some(&ChessColor::WHITE))
+        );
+        let old_hello = NetMessage::Hello {
+            version: 0,
+            color: ChessColor::WHITE,
+        };
+        assert_eq!(
+            Err(NetProtocolError::VersionMismatch(0)),
+            NetProtocol::check_hello(&old_hello, Some(&ChessColor::BLACK))
+        );
+    }
+
+    #[test]
+    fn test_net_protocol_moves() {
+        let (mut pieces, mut move_state) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        let message = |index: usize, uci: &str| {
+            let (from, to, promotion) = Notation::move_from_uci(uci).unwrap();
+            NetMessage::Move {
+                index,
+                from,
+                to,
+                promotion,
+            }
+        };
+
+        // black may not move first and the index has to follow the history
+        for (remote_color, index) in [(ChessColor::BLACK, 0), (ChessColor::WHITE, 1)] {
+            assert!(matches!(
+                NetProtocol::apply_move(
+                    &mut pieces,
+                    &mut move_state,
+                    &remote_color,
+                    &message(index, "e2e4")
+                ),
+                Err(NetProtocolError::IllegalMove { .. })
+            ));
+        }
+        assert_eq!(
+            Err(NetProtocolError::IllegalMove {
+                index: 0,
+                uci: "e2e5".to_string()
+            }),
+            NetProtocol::apply_move(
+                &mut pieces,
+                &mut move_state,
+                &ChessColor::WHITE,
+                &message(0, "e2e5")
+            )
+            .map(|chess_move| chess_move.san)
+        );
+
+        let chess_move = NetProtocol::apply_move(
+            &mut pieces,
+            &mut move_state,
+            &ChessColor::WHITE,
+            &message(0, "e2e4"),
+        )
+        .unwrap();
+        assert_eq!("e4", chess_move.san);
+        move_state.next_move();
+        assert_eq!(
+            Some(message(0, "e2e4")),
+            NetProtocol::move_message(&move_state, 0)
+        );
+    }
+
+    #[test]
+    fn test_net_protocol_promotion_without_piece() {
+        let (mut pieces, mut move_state) =
+            FenParser::parse_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let message = |uci: &str| {
+            let (from, to, promotion) = Notation::move_from_uci(uci).unwrap();
+            NetMessage::Move {
+                index: 0,
+                from,
+                to,
+                promotion,
+            }
+        };
+        let fen = FenParser::save_fen(&pieces.iter().collect(), &move_state);
+
+        assert_eq!(
+            Err(NetProtocolError::IllegalMove {
+                index: 0,
+                uci: "b7b8".to_string()
+            }),
+            NetProtocol::apply_move(
+                &mut pieces,
+                &mut move_state,
+                &ChessColor::WHITE,
+                &message("b7b8")
+            )
+            .map(|chess_move| chess_move.san)
+        );
+        assert!(move_state.move_history.is_empty());
+        assert_eq!(None, move_state.promotion_cell);
+        assert_eq!(
+            fen,
+            FenParser::save_fen(&pieces.iter().collect(), &move_state)
+        );
+
+        let chess_move = NetProtocol::apply_move(
+            &mut pieces,
+            &mut move_state,
+            &ChessColor::WHITE,
+            &message("b7b8n"),
+        )
+        .unwrap();
+        assert_eq!("b8=N", chess_move.san);
+        assert_eq!(None, move_state.promotion_cell);
+    }
+
+    #[test]
+    fn test_net_protocol_sync() {
+        let sync = NetMessage::SyncState {
+            fen: START_POSITION_FEN.to_string(),
+            moves: ["e2e4", "e7e5", "g1f3"].map(str::to_string).to_vec(),
+        };
+        let (pieces, move_state) = NetProtocol::apply_sync(&sync).unwrap();
+        assert_eq!(3, move_state.move_history.len());
+        assert_eq!(sync, NetProtocol::sync_message(&pieces, &move_state));
+
+        let illegal_sync = NetMessage::SyncState {
+            fen: START_POSITION_FEN.to_string(),
+            moves: ["e2e4", "e2e4"].map(str::to_string).to_vec(),
+        };
+        assert_eq!(
+            Err(NetProtocolError::IllegalMove {
+                index: 1,
+                uci: "e2e4".to_string()
+            }),
+            NetProtocol::apply_sync(&illegal_sync).map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,571 @@
+use crate::{
+    chess_move_state::MoveState,
+    chess_piece::{ChessPiece, PieceType},
+    common_chess::CellPosition,
+};
+
+const FILES: [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+const RANKS: [char; 8] = ['1', '2', '3', '4', '5', '6', '7', '8'];
+// pieces a pawn may become on the last rank
+pub const PROMOTION_TYPES: [PieceType; 4] = [
+    PieceType::QUEEN,
+    PieceType::ROOK,
+    PieceType::BISHOP,
+    PieceType::KNIGHT,
+];
+
+// textual forms of a move:
+//   SAN "Nbd7", "exd5", "e8=Q+"
+//   LAN "Nb8-d7", "e4xd5", "e7-e8=Q+"
+//   UCI "b8d7", "e4d5", "e7e8q"
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveNotation {
+    SAN,
+    LAN,
+    UCI,
+}
+
+pub struct Notation;
+
+impl Notation {
+    // letter of the file of a column, 'a' for the first one
+    pub fn file_char(i: i8) -> char {
+        FILES[i as usize]
+    }
+
+    // digit of the rank of a row, '1' for the first one
+    pub fn rank_char(j: i8) -> char {
+        RANKS[j as usize]
+    }
+
+    // "e4" style name of a cell
+    pub fn cell_to_string(pos: &CellPosition) -> String {
+        format!(
+            "{}{}",
+            Notation::file_char(pos.i),
+            Notation::rank_char(pos.j)
+        )
+    }
+
+    pub fn cell_from_string(name: &str) -> Option<CellPosition> {
+        let mut chars = name.chars();
+        let file = chars.next()?;
+        let rank = chars.next()?;
+        if chars.next().is_some() {
+            return None;
+        }
+        let i = FILES.iter().position(|f| *f == file)?;
+        let j = RANKS.iter().position(|r| *r == rank)?;
+        Some(CellPosition {
+            i: i as i8,
+            j: j as i8,
+        })
+    }
+
+    pub fn piece_letter(piece_type: &PieceType) -> &'static str {
+        match piece_type {
+            PieceType::PAWN => "",
+            PieceType::KNIGHT => "N",
+            PieceType::BISHOP => "B",
+            PieceType::ROOK => "R",
+            PieceType::QUEEN => "Q",
+            PieceType::KING => "K",
+        }
+    }
+
+    // SAN of a legal move in the position before it is made, without the check suffix
+    pub fn move_to_san(
+        piece: &ChessPiece,
+        to: &CellPosition,
+        pieces: &Vec<&ChessPiece>,
+        move_state: &MoveState,
+    ) -> String {
+        if let Some((rook_from, _)) = piece.castling_rook_move(to) {
+            return if rook_from.i > piece.pos.i {
+                "O-O".to_string()
+            } else {
+                "O-O-O".to_string()
+            };
+        }
+
+        let is_capture = pieces.iter().any(|cp| cp.pos == *to)
+            || (piece.piece_type == PieceType::PAWN && piece.pos.i != to.i);
+        let mut san = Notation::piece_letter(&piece.piece_type).to_string();
+
+        if piece.piece_type == PieceType::PAWN {
+            if is_capture {
+                san.push(FILES[piece.pos.i as usize]);
+            }
+        } else {
+            let rivals: Vec<&ChessPiece> = pieces
+                .iter()
+                .filter(|cp| {
+                    cp.pos != piece.pos
+                        && cp.color == piece.color
+                        && cp.piece_type == piece.piece_type
+                        && cp.get_legal_cells_for_move(pieces, move_state).contains(to)
+                })
+                .copied()
+                .collect();
+            if !rivals.is_empty() {
+                let same_file = rivals.iter().any(|cp| cp.pos.i == piece.pos.i);
+                let same_rank = rivals.iter().any(|cp| cp.pos.j == piece.pos.j);
+                if !same_file {
+                    san.push(FILES[piece.pos.i as usize]);
+                } else if !same_rank {
+                    san.push_str(&(piece.pos.j + 1).to_string());
+                } else {
+                    san.push_str(&Notation::cell_to_string(&piece.pos));
+                }
+            }
+        }
+
+        if is_capture {
+            san.push('x');
+        }
+        san.push_str(&Notation::cell_to_string(to));
+        san
+    }
+
+    // legal move of the side to move matching the SAN, annotations and suffixes are ignored
+    pub fn move_from_san(
+        san: &str,
+        pieces: &Vec<&ChessPiece>,
+        move_state: &MoveState,
+    ) -> Option<(CellPosition, CellPosition, Option<PieceType>)> {
+        let normalize = |san: &str| -> String {
+            san.trim_end_matches(['+', '#', '!', '?'])
+                .replace('0', "O")
+                .replace('=', "")
+        };
+        let expected = normalize(san);
+
+        for piece in pieces
+            .iter()
+            .filter(|cp| cp.color == move_state.current_collor)
+        {
+            for to in piece.get_legal_cells_for_move(pieces, move_state) {
+                let move_san = Notation::move_to_san(piece, &to, pieces, move_state);
+                if !piece.is_promotion_move(&to) {
+                    if normalize(&move_san) == expected {
+                        return Some((piece.pos, to, None));
+                    }
+                    continue;
+                }
+                for piece_type in PROMOTION_TYPES {
+                    let promotion_san = move_san.clone() + &Notation::promotion_suffix(&piece_type);
+                    if normalize(&promotion_san) == expected {
+                        return Some((piece.pos, to, Some(piece_type)));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // "e7e8q" style move as used by UCI and perft divide output
+    pub fn move_to_uci(
+        from: &CellPosition,
+        to: &CellPosition,
+        promotion: &Option<PieceType>,
+    ) -> String {
+        let promotion_letter = promotion
+            .as_ref()
+            .map(|piece_type| Notation::piece_letter(piece_type).to_lowercase())
+            .unwrap_or_default();
+        format!(
+            "{}{}{}",
+            Notation::cell_to_string(from),
+            Notation::cell_to_string(to),
+            promotion_letter
+        )
+    }
+
+    // move of a "e7e8q" style string, its legality is left to make_move
+    pub fn move_from_uci(uci: &str) -> Option<(CellPosition, CellPosition, Option<PieceType>)> {
+        if !uci.is_ascii() || !(4..=5).contains(&uci.len()) {
+            return None;
+        }
+        let from = Notation::cell_from_string(&uci[0..2])?;
+        let to = Notation::cell_from_string(&uci[2..4])?;
+        let promotion = match &uci[4..] {
+            "" => None,
+            "q" => Some(PieceType::QUEEN),
+            "r" => Some(PieceType::ROOK),
+            "b" => Some(PieceType::BISHOP),
+            "n" => Some(PieceType::KNIGHT),
+            _ => return None,
+        };
+        Some((from, to, promotion))
+    }
+
+    // whole text of a legal move in the position before it, with the promotion piece
+    // and the check or mate suffix; None for an illegal move or a promotion without its piece
+    pub fn format_move(
+        notation: MoveNotation,
+        from: &CellPosition,
+        to: &CellPosition,
+        promotion: &Option<PieceType>,
+        pieces: &Vec<&ChessPiece>,
+        move_state: &MoveState,
+    ) -> Option<String> {
+        let mut pieces_after_move: Vec<ChessPiece> =
+            pieces.iter().map(|cp| (*cp).clone()).collect();
+        let mut move_state_after_move = move_state.clone();
+        let chess_move =
+            move_state_after_move.make_move(&mut pieces_after_move, from, to, promotion.clone())?;
+        if move_state_after_move.promotion_cell.is_some() {
+            return None;
+        }
+        match notation {
+            MoveNotation::SAN => Some(chess_move.san),
+            MoveNotation::UCI => Some(Notation::move_to_uci(from, to, &chess_move.promotion)),
+            MoveNotation::LAN if chess_move.castling_rook.is_some() => Some(chess_move.san),
+            MoveNotation::LAN => {
+                let separator = match chess_move.captured_piece {
+                    Some(_) => 'x',
+                    None => '-',
+                };
+                let promotion_suffix = chess_move
+                    .promotion
+                    .as_ref()
+                    .map(Notation::promotion_suffix)
+                    .unwrap_or_default();
+                Some(format!(
+                    "{}{}{}{}{}{}",
+                    Notation::piece_letter(&chess_move.piece.piece_type),
+                    Notation::cell_to_string(from),
+                    separator,
+                    Notation::cell_to_string(to),
+                    promotion_suffix,
+                    Notation::check_suffix(&move_state_after_move)
+                ))
+            }
+        }
+    }
+
+    // legal move of the side to move written in the notation, suffixes are optional
+    pub fn parse_move(
+        notation: MoveNotation,
+        text: &str,
+        pieces: &Vec<&ChessPiece>,
+        move_state: &MoveState,
+    ) -> Option<(CellPosition, CellPosition, Option<PieceType>)> {
+        let parsed_move = match notation {
+            MoveNotation::SAN => return Notation::move_from_san(text, pieces, move_state),
+            MoveNotation::UCI => Notation::move_from_uci(text)?,
+            MoveNotation::LAN => {
+                let text = text.trim_end_matches(['+', '#', '!', '?']);
+                if matches!(text, "O-O" | "O-O-O" | "0-0" | "0-0-0") {
+                    return Notation::move_from_san(text, pieces, move_state);
+                }
+                Notation::move_from_lan(text, pieces)?
+            }
+        };
+        let (from, to, promotion) = &parsed_move;
+        let piece = pieces
+            .iter()
+            .find(|cp| cp.pos == *from && cp.color == move_state.current_collor)?;
+        if !piece
+            .get_legal_cells_for_move(pieces, move_state)
+            .contains(to)
+            || piece.is_promotion_move(to) != promotion.is_some()
+        {
+            return None;
+        }
+        Some(parsed_move)
+    }
+
+    // "Ng1-f3" or "e7xd8=Q" without suffixes, the piece letter and capture have to
+    // match the pieces, legality is checked by the caller
+    fn move_from_lan(
+        lan: &str,
+        pieces: &Vec<&ChessPiece>,
+    ) -> Option<(CellPosition, CellPosition, Option<PieceType>)> {
+        if !lan.is_ascii() {
+            return None;
+        }
+        let (piece_letter, lan) = match lan.chars().next()? {
+            letter if letter.is_ascii_uppercase() => (&lan[..1], &lan[1..]),
+            _ => ("", lan),
+        };
+        if lan.len() < 5 {
+            return None;
+        }
+        let from = Notation::cell_from_string(&lan[0..2])?;
+        let is_capture = match &lan[2..3] {
+            "-" => false,
+            "x" => true,
+            _ => return None,
+        };
+        let to = Notation::cell_from_string(&lan[3..5])?;
+        let promotion = match &lan[5..] {
+            "" => None,
+            suffix => Some(
+                PROMOTION_TYPES
+                    .into_iter()
+                    .find(|piece_type| Notation::promotion_suffix(piece_type) == suffix)?,
+            ),
+        };
+
+        let piece = pieces.iter().find(|cp| cp.pos == from)?;
+        let is_en_passant = piece.piece_type == PieceType::PAWN && from.i != to.i;
+        if Notation::piece_letter(&piece.piece_type) != piece_letter
+            || is_capture != (pieces.iter().any(|cp| cp.pos == to) || is_en_passant)
+        {
+            return None;
+        }
+        Some((from, to, promotion))
+    }
+
+    pub fn promotion_suffix(piece_type: &PieceType) -> String {
+        format!("={}", Notation::piece_letter(piece_type))
+    }
+
+    // "+" or "#" for the position after a move
+    pub fn check_suffix(move_state: &MoveState) -> &'static str {
+        if move_state.mate_state.is_some() {
+            "#"
+        } else if move_state.check_state.is_some() {
+            "+"
+        } else {
+            ""
+        }
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use super::*;
+    use crate::fen_parser::FenParser;
+
+    fn san_for(fen: &str, from: &str, to: &str) -> String {
+        let (pieces, move_state) = FenParser::parse_fen(fen).unwrap();
+        let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+        let from = Notation::cell_from_string(from).unwrap();
+        let to = Notation::cell_from_string(to).unwrap();
+        let piece = pieces.iter().find(|cp| cp.pos == from).unwrap();
+        Notation::move_to_san(piece, &to, &pieces, &move_state)
+    }
+
+    #[test]
+    fn test_move_to_san() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!("e4", san_for(start, "e2", "e4"));
+        assert_eq!("Nf3", san_for(start, "g1", "f3"));
+        assert_eq!(
+            "exd5",
+            san_for("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4", "d5")
+        );
+        assert_eq!(
+            "exd6",
+            san_for("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5", "d6")
+        );
+        assert_eq!("O-O", san_for("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "g1"));
+        assert_eq!(
+            "O-O-O",
+            san_for("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1", "c1")
+        );
+    }
+
+    #[test]
+    fn test_move_from_san() {
+        let (pieces, move_state) =
+            FenParser::parse_fen("r3k3/1P6/8/8/8/8/8/R3K2R w KQq - 0 1").unwrap();
+        let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+        let cell = |name: &str| Notation::cell_from_string(name).unwrap();
+
+        assert_eq!(
+            Some((cell("e1"), cell("g1"), None)),
+            Notation::move_from_san("0-0", &pieces, &move_state)
+        );
+        assert_eq!(
+            Some((cell("b7"), cell("a8"), Some(PieceType::KNIGHT))),
+            Notation::move_from_san("bxa8=N+!", &pieces, &move_state)
+        );
+        assert_eq!(
+            Some((cell("a1"), cell("a7"), None)),
+            Notation::move_from_san("Ra7", &pieces, &move_state)
+        );
+        assert_eq!(None, Notation::move_from_san("Rh9", &pieces, &move_state));
+    }
+
+    #[test]
+    fn test_move_uci() {
+        let cell = |name: &str| Notation::cell_from_string(name).unwrap();
+        assert_eq!(
+            Some((cell("e2"), cell("e4"), None)),
+            Notation::move_from_uci("e2e4")
+        );
+        assert_eq!(
+            Some((cell("e7"), cell("e8"), Some(PieceType::KNIGHT))),
+            Notation::move_from_uci("e7e8n")
+        );
+        assert_eq!(None, Notation::move_from_uci("e7e8k"));
+        assert_eq!(None, Notation::move_from_uci("e2e9"));
+        assert_eq!(None, Notation::move_from_uci("0000"));
+        for uci in ["a1h8", "h2h1q", "b7b8r"] {
+            let (from, to, promotion) = Notation::move_from_uci(uci).unwrap();
+            assert_eq!(uci, Notation::move_to_uci(&from, &to, &promotion));
+        }
+    }
+
+    #[test]
+    fn test_move_to_san_disambiguation() {
+        assert_eq!(
+            "Rad1",
+            san_for("4k3/8/8/8/8/8/8/R4RK1 w - - 0 1", "a1", "d1")
+        );
+        assert_eq!(
+            "R1a3",
+            san_for("4k3/8/R7/8/8/8/8/R5K1 w - - 0 1", "a1", "a3")
+        );
+        assert_eq!(
+            "Qh4e1",
+            san_for("4k3/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", "h4", "e1")
+        );
+    }
+
+    // every legal move of the side to move, promotions once per piece
+    fn legal_moves(
+        pieces: &Vec<&ChessPiece>,
+        move_state: &MoveState,
+    ) -> Vec<(CellPosition, CellPosition, Option<PieceType>)> {
+        let mut moves = Vec::new();
+        for piece in pieces
+            .iter()
+            .filter(|cp| cp.color == move_state.current_collor)
+        {
+            for to in piece.get_legal_cells_for_move(pieces, move_state) {
+                if piece.is_promotion_move(&to) {
+                    for piece_type in PROMOTION_TYPES {
+                        moves.push((piece.pos, to, Some(piece_type)));
+                    }
+                } else {
+                    moves.push((piece.pos, to, None));
+                }
+            }
+        }
+        moves
+    }
+
+    #[test]
+    fn test_move_notation_round_trip() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "4k3/1P6/8/2pP4/8/8/3N4/R3K1N1 w Q c6 0 1",
+        ];
+        for fen in fens {
+            let (pieces, move_state) = FenParser::parse_fen(fen).unwrap();
+            let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+            for legal_move in legal_moves(&pieces, &move_state) {
+                let (from, to, promotion) = &legal_move;
+                for notation in [MoveNotation::SAN, MoveNotation::LAN, MoveNotation::UCI] {
+                    let text =
+                        Notation::format_move(notation, from, to, promotion, &pieces, &move_state)
+                            .unwrap();
+                    assert_eq!(
+                        Some(legal_move.clone()),
+                        Notation::parse_move(notation, &text, &pieces, &move_state),
+                        "{:?} {} in {}",
+                        notation,
+                        text,
+                        fen
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_move() {
+        let (pieces, move_state) =
+            FenParser::parse_fen("4k3/1P6/8/2pP4/8/8/3N4/R3K1N1 w Q c6 0 1").unwrap();
+        let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+        let cell = |name: &str| Notation::cell_from_string(name).unwrap();
+        let format = |notation, from, to, promotion| {
+            Notation::format_move(
+                notation,
+                &cell(from),
+                &cell(to),
+                &promotion,
+                &pieces,
+                &move_state,
+            )
+        };
+
+        let queen = Some(PieceType::QUEEN);
+        assert_eq!(
+            Some("b8=Q+".to_string()),
+            format(MoveNotation::SAN, "b7", "b8", queen.clone())
+        );
+        assert_eq!(
+            Some("b7-b8=Q+".to_string()),
+            format(MoveNotation::LAN, "b7", "b8", queen.clone())
+        );
+        assert_eq!(
+            Some("b7b8q".to_string()),
+            format(MoveNotation::UCI, "b7", "b8", queen)
+        );
+        assert_eq!(
+            Some("d5xc6".to_string()),
+            format(MoveNotation::LAN, "d5", "c6", None)
+        );
+        assert_eq!(
+            Some("Ngf3".to_string()),
+            format(MoveNotation::SAN, "g1", "f3", None)
+        );
+        assert_eq!(
+            Some("Ng1-f3".to_string()),
+            format(MoveNotation::LAN, "g1", "f3", None)
+        );
+        assert_eq!(
+            Some("O-O-O".to_string()),
+            format(MoveNotation::LAN, "e1", "c1", None)
+        );
+        assert_eq!(None, format(MoveNotation::SAN, "b7", "b8", None));
+        assert_eq!(None, format(MoveNotation::UCI, "e1", "e3", None));
+    }
+
+    #[test]
+    fn test_parse_move_rejects() {
+        let (pieces, move_state) =
+            FenParser::parse_fen("4k3/1P6/8/2pP4/8/8/3N4/R3K1N1 w Q c6 0 1").unwrap();
+        let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+        for (notation, text) in [
+            (MoveNotation::LAN, "Bg1-f3"),
+            (MoveNotation::LAN, "Ng1xf3"),
+            (MoveNotation::LAN, "d5-c6"),
+            (MoveNotation::LAN, "b7-b8"),
+            (MoveNotation::LAN, "e8-e7"),
+            (MoveNotation::UCI, "b7b8"),
+            (MoveNotation::UCI, "e1e3"),
+            (MoveNotation::UCI, "d5d6q"),
+            (MoveNotation::SAN, "Nf3"),
+        ] {
+            assert_eq!(
+                None,
+                Notation::parse_move(notation, text, &pieces, &move_state),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_file_and_rank_chars() {
+        let labels: String = (0..8).map(Notation::file_char).collect();
+        assert_eq!("abcdefgh", labels);
+        let labels: String = (0..8).map(Notation::rank_char).collect();
+        assert_eq!("12345678", labels);
+        for name in ["a1", "h8", "e4"] {
+            let cell = Notation::cell_from_string(name).unwrap();
+            assert_eq!(name, Notation::cell_to_string(&cell));
+        }
+        assert_eq!(None, Notation::cell_from_string("e0"));
+        assert_eq!(None, Notation::cell_from_string("i1"));
+    }
+}
@@ -0,0 +1,192 @@
+use std::time::Instant;
+
+use crate::{
+    ai_engine::{AiEngine, EngineMove},
+    chess_move_state::MoveState,
+    chess_piece::ChessPiece,
+    fen_parser::{FenParser, START_POSITION_FEN},
+    notation::Notation,
+};
+
+pub struct Perft;
+
+impl Perft {
+    // number of leaf positions reached by all legal move sequences of the given length
+    pub fn perft(pieces: &mut Vec<ChessPiece>, move_state: &mut MoveState, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = AiEngine::legal_moves(pieces, move_state);
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves
+            .iter()
+            .map(|engine_move| Perft::perft_after_move(pieces, move_state, engine_move, depth))
+            .sum()
+    }
+
+    // leaf count for every legal move of the position
+    pub fn divide(
+        pieces: &mut Vec<ChessPiece>,
+        move_state: &mut MoveState,
+        depth: u32,
+    ) -> Vec<(EngineMove, u64)> {
+        AiEngine::legal_moves(pieces, move_state)
+            .into_iter()
+            .map(|engine_move| {
+                let nodes = Perft::perft_after_move(pieces, move_state, &engine_move, depth);
+                (engine_move, nodes)
+            })
+            .collect()
+    }
+
+    fn perft_after_move(
+        pieces: &mut Vec<ChessPiece>,
+        move_state: &mut MoveState,
+        engine_move: &EngineMove,
+        depth: u32,
+    ) -> u64 {
+        let chess_move = move_state.make_move(
+            pieces,
+            &engine_move.from,
+            &engine_move.to,
+            engine_move.promotion.clone(),
+        );
+        if chess_move.is_none() {
+            return 0;
+        }
+        move_state.next_move();
+        let nodes = Perft::perft(pieces, move_state, depth - 1);
+        move_state.undo_move(pieces);
+        nodes
+    }
+
+    // `perft <depth> [<fen>]`, prints the divide output and the total node count
+    pub fn run_command(args: &[String]) -> Result<(), String> {
+        let usage = "usage: perft <depth> [<fen>]";
+        let depth: u32 = args
+            .first()
+            .and_then(|depth| depth.parse().ok())
+            .filter(|depth| *depth > 0)
+            .ok_or(usage)?;
+        let fen = match args[1..].join(" ") {
+            fen if fen.is_empty() => START_POSITION_FEN.to_string(),
+            fen => fen,
+        };
+        let (mut pieces, mut move_state) =
+            FenParser::parse_fen(&fen).map_err(|err| format!("invalid FEN '{}': {}", fen, err))?;
+
+        let start = Instant::now();
+        let divide = Perft::divide(&mut pieces, &mut move_state, depth);
+        for (engine_move, nodes) in divide.iter() {
+            let uci_move =
+                Notation::move_to_uci(&engine_move.from, &engine_move.to, &engine_move.promotion);
+            println!("{}: {}", uci_move, nodes);
+        }
+        let nodes: u64 = divide.iter().map(|(_, nodes)| nodes).sum();
+        println!();
+        println!("Nodes searched: {}", nodes);
+        println!("Time: {} ms", start.elapsed().as_millis());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use super::*;
+
+    // known node counts, https://www.chessprogramming.org/Perft_Results
+    fn assert_perft(fen: &str, expected_nodes: &[u64]) {
+        let (mut pieces, mut move_state) = FenParser::parse_fen(fen).unwrap();
+        for (depth, expected) in expected_nodes.iter().enumerate() {
+            let nodes = Perft::perft(&mut pieces, &mut move_state, depth as u32 + 1);
+            assert_eq!(*expected, nodes, "{} at depth {}", fen, depth + 1);
+        }
+        assert_eq!(
+            fen,
+            FenParser::save_fen(&pieces.iter().collect(), &move_state)
+        );
+    }
+
+    #[test]
+    fn test_perft_initial_position() {
+        assert_perft(START_POSITION_FEN, &[20, 400, 8_902]);
+    }
+
+    #[test]
+    fn test_perft_kiwipete() {
+        assert_perft(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2_039, 97_862],
+        );
+    }
+
+    #[test]
+    fn test_perft_en_passant_pins() {
+        assert_perft(
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2_812],
+        );
+    }
+
+    #[test]
+    fn test_perft_promotions_and_castling() {
+        assert_perft(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9_467],
+        );
+    }
+
+    #[test]
+    fn test_perft_discovered_checks() {
+        assert_perft(
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1_486, 62_379],
+        );
+    }
+
+    #[test]
+    fn test_perft_middlegame() {
+        assert_perft(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2_079, 89_890],
+        );
+    }
+
+    // deeper counts of the same positions, slow in debug builds:
+    // cargo test --release -- --ignored
+    #[test]
+    #[ignore]
+    fn test_perft_depth_4() {
+        assert_perft(START_POSITION_FEN, &[20, 400, 8_902, 197_281]);
+        assert_perft(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2_039, 97_862, 4_085_603],
+        );
+        assert_perft(
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2_812, 43_238, 674_624],
+        );
+        assert_perft(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9_467, 422_333],
+        );
+        assert_perft(
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1_486, 62_379, 2_103_487],
+        );
+        assert_perft(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2_079, 89_890, 3_894_594],
+        );
+    }
+
+    #[test]
+    fn test_perft_divide() {
+        let (mut pieces, mut move_state) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        let divide = Perft::divide(&mut pieces, &mut move_state, 2);
+        assert_eq!(20, divide.len());
+        assert!(divide.iter().all(|(_, nodes)| *nodes == 20));
+    }
+}
@@ -0,0 +1,456 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    chess_move_state::MoveState,
+    chess_piece::ChessPiece,
+    fen_parser::{FenParseError, FenParser, START_POSITION_FEN},
+    notation::Notation,
+};
+
+const LINE_LENGTH: usize = 80;
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PgnParseError {
+    InvalidTag(String),
+    UnterminatedComment,
+    UnbalancedVariation,
+    InvalidFen(FenParseError),
+    IllegalMove { ply: usize, san: String },
+}
+
+impl fmt::Display for PgnParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnParseError::InvalidTag(tag) => write!(f, "invalid tag pair '{}'", tag),
+            PgnParseError::UnterminatedComment => write!(f, "comment is not closed"),
+            PgnParseError::UnbalancedVariation => write!(f, "variation brackets do not match"),
+            PgnParseError::InvalidFen(err) => write!(f, "invalid FEN tag: {}", err),
+            PgnParseError::IllegalMove { ply, san } => {
+                write!(f, "illegal move '{}' at ply {}", san, ply + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PgnParseError {}
+
+// tag pairs and main line moves of a PGN game, comments, NAGs and variations are dropped
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<String>,
+    pub result: Option<String>,
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub struct PgnParser;
+
+impl PgnParser {
+    // reads the first game of the text
+    pub fn parse_pgn(pgn: &str) -> Result<PgnGame, PgnParseError> {
+        let mut game = PgnGame::default();
+        let mut chars = pgn.chars().peekable();
+        let mut variation_depth = 0;
+        let mut is_line_start = true;
+
+        while let Some(symbol) = chars.next() {
+            if symbol == '%' && is_line_start {
+                chars.by_ref().find(|c| *c == '\n');
+                continue;
+            }
+            is_line_start = symbol == '\n';
+            match symbol {
+                '[' if variation_depth == 0 && game.moves.is_empty() => {
+                    let tag = PgnParser::read_tag(&mut chars);
+                    game.tags.push(PgnParser::parse_tag(&tag)?);
+                }
+                '{' => {
+                    chars
+                        .by_ref()
+                        .find(|c| *c == '}')
+                        .ok_or(PgnParseError::UnterminatedComment)?;
+                }
+                ';' => {
+                    chars.by_ref().find(|c| *c == '\n');
+                    is_line_start = true;
+                }
+                '(' => variation_depth += 1,
+                ')' if variation_depth == 0 => return Err(PgnParseError::UnbalancedVariation),
+                ')' => variation_depth -= 1,
+                _ if symbol.is_whitespace() => {}
+                _ => {
+                    let mut token = symbol.to_string();
+                    while let Some(next) =
+                        chars.next_if(|c| !c.is_whitespace() && !"{}();[]".contains(*c))
+                    {
+                        token.push(next);
+                    }
+                    if variation_depth > 0 || token.starts_with('$') {
+                        continue;
+                    }
+                    if RESULTS.contains(&token.as_str()) {
+                        game.result = Some(token);
+                        break;
+                    }
+                    // move numbers like "12." or "12..." may be glued to the move
+                    let san = token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+                    if !san.is_empty() {
+                        game.moves.push(san.to_string());
+                    }
+                }
+            }
+        }
+        if variation_depth != 0 {
+            return Err(PgnParseError::UnbalancedVariation);
+        }
+        Ok(game)
+    }
+
+    // text of a tag up to its closing bracket, brackets in the quoted value belong to it
+    fn read_tag(chars: &mut impl Iterator<Item = char>) -> String {
+        let mut tag = String::new();
+        let mut is_in_value = false;
+        let mut is_escaped = false;
+        for symbol in chars {
+            match symbol {
+                ']' if !is_in_value => break,
+                '"' if !is_escaped => is_in_value = !is_in_value,
+                _ => {}
+            }
+            is_escaped = is_in_value && symbol == '\\' && !is_escaped;
+            tag.push(symbol);
+        }
+        tag
+    }
+
+    fn parse_tag(tag: &str) -> Result<(String, String), PgnParseError> {
+        let invalid_tag = || PgnParseError::InvalidTag(tag.to_string());
+        let (name, value) = tag
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(invalid_tag)?;
+        let value = value
+            .trim()
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .ok_or_else(invalid_tag)?;
+        Ok((
+            name.to_string(),
+            value.replace("\\\"", "\"").replace("\\\\", "\\"),
+        ))
+    }
+
+    // position after the first plies of the game, checking every move against the rules
+    pub fn replay(
+        game: &PgnGame,
+        ply: Option<usize>,
+    ) -> Result<(Vec<ChessPiece>, MoveState), PgnParseError> {
+        let start_fen = game.tag("FEN").unwrap_or(START_POSITION_FEN);
+        let (mut pieces, mut move_state) =
+            FenParser::parse_fen(start_fen).map_err(PgnParseError::InvalidFen)?;
+
+        let ply = ply.unwrap_or(game.moves.len()).min(game.moves.len());
+        for (index, san) in game.moves[..ply].iter().enumerate() {
+            let illegal_move = || PgnParseError::IllegalMove {
+                ply: index,
+                san: san.clone(),
+            };
+            let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+            let (from, to, promotion) =
+                Notation::move_from_san(san, &piece_refs, &move_state).ok_or_else(illegal_move)?;
+            move_state
+                .make_move(&mut pieces, &from, &to, promotion)
+                .ok_or_else(illegal_move)?;
+            move_state.next_move();
+        }
+        Ok((pieces, move_state))
+    }
+
+    // seven tag roster, movetext and result of the game
+    pub fn save_pgn(move_state: &MoveState, date: &str) -> String {
+        PgnParser::save_pgn_with_tags(move_state, date, &[])
+    }
+
+    // tags of the roster are replaced by the given ones of the same name,
+    // the others follow the roster
+    pub fn save_pgn_with_tags(
+        move_state: &MoveState,
+        date: &str,
+        tags: &[(&str, String)],
+    ) -> String {
+        let result = move_state.result();
+        let mut roster = [
+            ("Event", "Casual Game"),
+            ("Site", "?"),
+            ("Date", date),
+            ("Round", "-"),
+            ("White", "?"),
+            ("Black", "?"),
+            ("Result", result),
+        ];
+        for (tag, value) in roster.iter_mut() {
+            if let Some((_, custom_value)) = tags.iter().find(|(name, _)| name == tag) {
+                *value = custom_value;
+            }
+        }
+        let extra_tags = tags
+            .iter()
+            .filter(|(name, _)| roster.iter().all(|(tag, _)| tag != name))
+            .map(|(name, value)| (*name, value.as_str()));
+
+        let mut pgn = String::new();
+        for (tag, value) in roster.into_iter().chain(extra_tags) {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            pgn.push_str(&format!("[{} \"{}\"]\n", tag, value));
+        }
+        let start_fen = move_state
+            .start_fen
+            .as_deref()
+            .unwrap_or(START_POSITION_FEN);
+        if start_fen != START_POSITION_FEN {
+            pgn.push_str("[SetUp \"1\"]\n");
+            pgn.push_str(&format!("[FEN \"{}\"]\n", start_fen));
+        }
+        pgn.push('\n');
+
+        let start_ply = move_state.start_ply();
+        let mut tokens = Vec::new();
+        for (index, chess_move) in move_state.move_history.iter().enumerate() {
+            let ply = start_ply + index as u32;
+            let move_number = ply / 2 + 1;
+            if ply.is_multiple_of(2) {
+                tokens.push(format!("{}.", move_number));
+            } else if index == 0 {
+                tokens.push(format!("{}...", move_number));
+            }
+            tokens.push(chess_move.san.clone());
+        }
+        tokens.push(result.to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + token.len() + 1 > LINE_LENGTH {
+                pgn.push_str(&line);
+                pgn.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        pgn.push_str(&line);
+        pgn.push('\n');
+        pgn
+    }
+
+    // "YYYY.MM.DD" date of the given time in UTC
+    pub fn pgn_date(time: SystemTime) -> String {
+        let days = time
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() / 86400) as i64;
+        // civil from days, http://howardhinnant.github.io/date_algorithms.html
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z.rem_euclid(146097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+        format!("{:04}.{:02}.{:02}", year, month, day)
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        castling_rights::CastlingRights,
+        chess_move::ChessMove,
+        chess_piece::{ChessPiece, PieceType},
+        common_chess::{CellPosition, ChessColor},
+    };
+
+    fn chess_move(san: &str) -> ChessMove {
+        ChessMove {
+            piece: ChessPiece::new(0, 0, ChessColor::WHITE, PieceType::PAWN),
+            to: CellPosition { i: 0, j: 0 },
+            captured_piece: None,
+            promotion: None,
+            castling_rook: None,
+            is_en_passant: false,
+            san: san.to_string(),
+            previous_castling_rights: CastlingRights::default(),
+            previous_en_passant_target: None,
+            previous_halfmove_clock: 0,
+        }
+    }
+
+    #[test]
+    fn test_save_pgn() {
+        let move_state = MoveState {
+            mate_state: Some(ChessColor::WHITE),
+            move_history: ["f3", "e5", "g4", "Qh4#"].map(chess_move).to_vec(),
+            ..Default::default()
+        };
+        let pgn = PgnParser::save_pgn(&move_state, "2024.01.02");
+        assert_eq!(
+            "[Event \"Casual Game\"]\n[Site \"?\"]\n[Date \"2024.01.02\"]\n[Round \"-\"]\n\
+             [White \"?\"]\n[Black \"?\"]\n[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n",
+            pgn
+        );
+    }
+
+    #[test]
+    fn test_save_pgn_with_tags() {
+        let move_state = MoveState {
+            move_history: ["e4"].map(chess_move).to_vec(),
+            ..Default::default()
+        };
+        let pgn = PgnParser::save_pgn_with_tags(
+            &move_state,
+            "2024.01.02",
+            &[
+                ("White", "Player \"one\"".to_string()),
+                ("Ply", "1".to_string()),
+            ],
+        );
+        assert!(pgn.contains(
+            "[White \"Player \\\"one\\\"\"]\n[Black \"?\"]\n[Result \"*\"]\n[Ply \"1\"]\n\n"
+        ));
+
+        let game = PgnParser::parse_pgn(&pgn).unwrap();
+        assert_eq!(Some("Player \"one\""), game.tag("White"));
+        assert_eq!(Some("1"), game.tag("Ply"));
+        assert_eq!(vec!["e4".to_string()], game.moves);
+    }
+
+    #[test]
+    fn test_save_pgn_flag_fall() {
+        let move_state = MoveState {
+            flag_state: Some(ChessColor::BLACK),
+            move_history: ["e4", "e5", "Nf3"].map(chess_move).to_vec(),
+            ..Default::default()
+        };
+        let pgn = PgnParser::save_pgn(&move_state, "2024.01.02");
+        assert!(pgn.contains("[Result \"1-0\"]\n"));
+        assert!(pgn.ends_with("\n1. e4 e5 2. Nf3 1-0\n"));
+    }
+
+    #[test]
+    fn test_save_pgn_from_position() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 12";
+        let move_state = MoveState {
+            start_fen: Some(fen.to_string()),
+            move_history: ["Kd7", "e4"].map(chess_move).to_vec(),
+            ..Default::default()
+        };
+        let pgn = PgnParser::save_pgn(&move_state, "2024.01.02");
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n"));
+        assert!(pgn.ends_with("\n12... Kd7 13. e4 *\n"));
+    }
+
+    #[test]
+    fn test_parse_pgn() {
+        let pgn = "[Event \"Casual \\\"Game\\\"\"]\n[White \"Morphy\"]\n\n\
+                   % escaped line\n\
+                   1. e4 {best by test} e5 $1 2.Nf3 (2. f4 exf4 (2... d5)) Nc6 ; rest of line\n\
+                   3. Bb5!? a6 1/2-1/2";
+        let game = PgnParser::parse_pgn(pgn).unwrap();
+        assert_eq!(Some("Casual \"Game\""), game.tag("Event"));
+        assert_eq!(Some("Morphy"), game.tag("White"));
+        assert_eq!(vec!["e4", "e5", "Nf3", "Nc6", "Bb5!?", "a6"], game.moves);
+        assert_eq!(Some("1/2-1/2".to_string()), game.result);
+
+        assert_eq!(
+            Err(PgnParseError::UnterminatedComment),
+            PgnParser::parse_pgn("1. e4 {open")
+        );
+        assert_eq!(
+            Err(PgnParseError::UnbalancedVariation),
+            PgnParser::parse_pgn("1. e4 (1. d4")
+        );
+    }
+
+    #[test]
+    fn test_replay_pgn() {
+        let game = PgnParser::parse_pgn("1. f3 e5 2. g4 Qh4# 0-1").unwrap();
+
+        let (_, move_state) = PgnParser::replay(&game, None).unwrap();
+        assert_eq!(Some(ChessColor::WHITE), move_state.mate_state);
+        assert_eq!("Qh4#", move_state.move_history[3].san);
+
+        let (pieces, move_state) = PgnParser::replay(&game, Some(2)).unwrap();
+        let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+        assert_eq!(
+            "rnbqkbnr/pppp1ppp/8/4p3/8/5P2/PPPPP1PP/RNBQKBNR w KQkq e6 0 2",
+            FenParser::save_fen(&pieces, &move_state)
+        );
+
+        let game = PgnParser::parse_pgn("1. e4 e5 2. Ke3").unwrap();
+        assert_eq!(
+            Err(PgnParseError::IllegalMove {
+                ply: 2,
+                san: "Ke3".to_string()
+            }),
+            PgnParser::replay(&game, None).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_parse_pgn_brackets_in_tag_value() {
+        let game = PgnParser::parse_pgn(
+            "[Event \"Open [A]\"]\n[Site \"Room \\\"]\\\" 2\"]\n[Round \"-\"]\n\n1. e4 *",
+        )
+        .unwrap();
+        assert_eq!(Some("Open [A]"), game.tag("Event"));
+        assert_eq!(Some("Room \"]\" 2"), game.tag("Site"));
+        assert_eq!(Some("-"), game.tag("Round"));
+        assert_eq!(vec!["e4".to_string()], game.moves);
+
+        let (_, move_state) = PgnParser::replay(&game, None).unwrap();
+        let pgn = PgnParser::save_pgn_with_tags(
+            &move_state,
+            "2024.01.02",
+            &[("Event", "Open [A] \"]\"".to_string())],
+        );
+        let game = PgnParser::parse_pgn(&pgn).unwrap();
+        assert_eq!(Some("Open [A] \"]\""), game.tag("Event"));
+        assert_eq!(vec!["e4".to_string()], game.moves);
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let game = PgnParser::parse_pgn(
+            "1. e4 d5 2. exd5 c6 3. dxc6 Nf6 4. cxb7 Nbd7 5. bxa8=Q Qc7 6. Qxc8+ Qxc8 *",
+        )
+        .unwrap();
+        let (_, move_state) = PgnParser::replay(&game, None).unwrap();
+        let pgn = PgnParser::save_pgn(&move_state, "2024.01.02");
+        assert_eq!(game.moves, PgnParser::parse_pgn(&pgn).unwrap().moves);
+    }
+
+    #[test]
+    fn test_pgn_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_251_200);
+        assert_eq!("2024.03.01", PgnParser::pgn_date(time));
+        assert_eq!("1970.01.01", PgnParser::pgn_date(UNIX_EPOCH));
+    }
+}
@@ -0,0 +1,357 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    chess_piece::{ChessPiece, PieceType},
+    common_chess::{CellPosition, ChessColor, FIRST_ELEMENT, LAST_ELEMENT},
+    notation::Notation,
+};
+
+const BOARD_SIZE: usize = 8;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PieceParseError {
+    WrongRowCount(usize),
+    // rank of the row and the number of cells in it
+    WrongColumnCount { rank: usize, count: usize },
+    UnknownSymbol { symbol: String, cell: CellPosition },
+    WrongKingCount { color: ChessColor, count: usize },
+    PawnOnBackRank(CellPosition),
+    // the side that has just moved can't be left in check
+    SideNotToMoveInCheck(ChessColor),
+}
+
+impl fmt::Display for PieceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PieceParseError::WrongRowCount(count) => write!(f, "expected 8 rows, found {}", count),
+            PieceParseError::WrongColumnCount { rank, count } => {
+                write!(f, "rank {} has {} cells instead of 8", rank, count)
+            }
+            PieceParseError::UnknownSymbol { symbol, cell } => write!(
+                f,
+                "unknown piece symbol '{}' on {}",
+                symbol,
+                Notation::cell_to_string(cell)
+            ),
+            PieceParseError::WrongKingCount { color, count } => {
+                write!(f, "{:?} has {} kings instead of one", color, count)
+            }
+            PieceParseError::PawnOnBackRank(cell) => {
+                write!(
+                    f,
+                    "pawn on the back rank at {}",
+                    Notation::cell_to_string(cell)
+                )
+            }
+            PieceParseError::SideNotToMoveInCheck(color) => {
+                write!(f, "{:?} is in check while the other side is to move", color)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PieceParseError {}
+
+pub struct PieceParser;
+
+impl PieceParser {
+    fn mappings<'a>() -> HashMap<&'a str, (ChessColor, PieceType)> {
+        HashMap::from([
+            ("b_pa", (ChessColor::BLACK, PieceType::PAWN)),
+            ("b_ro", (ChessColor::BLACK, PieceType::ROOK)),
+            ("b_kn", (ChessColor::BLACK, PieceType::KNIGHT)),
+            ("b_bi", (ChessColor::BLACK, PieceType::BISHOP)),
+            ("b_ki", (ChessColor::BLACK, PieceType::KING)),
+            ("b_qu", (ChessColor::BLACK, PieceType::QUEEN)),
+            ("w_pa", (ChessColor::WHITE, PieceType::PAWN)),
+            ("w_ro", (ChessColor::WHITE, PieceType::ROOK)),
+            ("w_kn", (ChessColor::WHITE, PieceType::KNIGHT)),
+            ("w_bi", (ChessColor::WHITE, PieceType::BISHOP)),
+            ("w_ki", (ChessColor::WHITE, PieceType::KING)),
+            ("w_qu", (ChessColor::WHITE, PieceType::QUEEN)),
+        ])
+    }
+
+    fn reverse_color_mappings<'a>() -> HashMap<ChessColor, &'a str> {
+        HashMap::from([(ChessColor::BLACK, "b"), (ChessColor::WHITE, "w")])
+    }
+    fn reverse_type_mappings<'a>() -> HashMap<PieceType, &'a str> {
+        HashMap::from([
+            (PieceType::PAWN, "pa"),
+            (PieceType::ROOK, "ro"),
+            (PieceType::KNIGHT, "kn"),
+            (PieceType::BISHOP, "bi"),
+            (PieceType::KING, "ki"),
+            (PieceType::QUEEN, "qu"),
+        ])
+    }
+
+    pub fn default_tile_map() -> String {
+        let string = "|b_ro|b_kn|b_bi|b_qu|b_ki|b_bi|b_kn|b_ro|\n
+                            |b_pa|b_pa|b_pa|b_pa|b_pa|b_pa|b_pa|b_pa|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |w_pa|w_pa|w_pa|w_pa|w_pa|w_pa|w_pa|w_pa|\n
+                            |w_ro|w_kn|w_bi|w_qu|w_ki|w_bi|w_kn|w_ro|\n
+                            ";
+        string.to_string()
+    }
+
+    pub fn test_tile_map() -> String {
+        let string = "|b_ro|b_kn|b_bi|b_qu|b_ki|none|none|none|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |none|none|none|none|none|none|none|none|\n
+                            |w_ro|w_kn|w_bi|w_qu|w_ki|none|none|none|\n
+                            ";
+        string.to_string()
+    }
+
+    // cells from a1 to h8 rank by rank, the map is a setup white starts from
+    pub fn parse_tile_map(map: String) -> Result<Vec<Option<ChessPiece>>, PieceParseError> {
+        let lines: Vec<&str> = map.split('\n').filter(|l| !l.trim().is_empty()).collect();
+        if lines.len() != BOARD_SIZE {
+            return Err(PieceParseError::WrongRowCount(lines.len()));
+        }
+        let mut cells = Vec::new();
+        for (j, line) in lines.iter().rev().enumerate() {
+            let symbols: Vec<&str> = line.split('|').filter(|l| !l.trim().is_empty()).collect();
+            if symbols.len() != BOARD_SIZE {
+                return Err(PieceParseError::WrongColumnCount {
+                    rank: j + 1,
+                    count: symbols.len(),
+                });
+            }
+            for (i, symbol) in symbols.iter().enumerate() {
+                cells.push(PieceParser::parse_piece(symbol.trim(), i, j)?);
+            }
+        }
+
+        let pieces: Vec<&ChessPiece> = cells.iter().flatten().collect();
+        PieceParser::validate_position(&pieces, &ChessColor::WHITE)?;
+        Ok(cells)
+    }
+
+    // a position the game can be played from with the given side to move
+    pub fn validate_position(
+        pieces: &[&ChessPiece],
+        side_to_move: &ChessColor,
+    ) -> Result<(), PieceParseError> {
+        for color in [ChessColor::WHITE, ChessColor::BLACK] {
+            let count = pieces
+                .iter()
+                .filter(|cp| cp.color == color && cp.piece_type == PieceType::KING)
+                .count();
+            if count != 1 {
+                return Err(PieceParseError::WrongKingCount { color, count });
+            }
+        }
+        if let Some(pawn) = pieces.iter().find(|cp| {
+            cp.piece_type == PieceType::PAWN
+                && (cp.pos.j == FIRST_ELEMENT || cp.pos.j == LAST_ELEMENT)
+        }) {
+            return Err(PieceParseError::PawnOnBackRank(pawn.pos));
+        }
+        let side_not_to_move = side_to_move.opposite();
+        if ChessPiece::is_king_under_check(&side_not_to_move, &pieces.to_vec()) {
+            return Err(PieceParseError::SideNotToMoveInCheck(side_not_to_move));
+        }
+        Ok(())
+    }
+
+    fn parse_piece(
+        symbol: &str,
+        i: usize,
+        j: usize,
+    ) -> Result<Option<ChessPiece>, PieceParseError> {
+        if symbol.eq("none") {
+            return Ok(None);
+        }
+        let mappings = PieceParser::mappings();
+        match mappings.get(symbol) {
+            Some((color, piece_type)) => Ok(Some(ChessPiece::new(
+                i as i8,
+                j as i8,
+                color.clone(),
+                piece_type.clone(),
+            ))),
+            None => Err(PieceParseError::UnknownSymbol {
+                symbol: symbol.to_string(),
+                cell: CellPosition {
+                    i: i as i8,
+                    j: j as i8,
+                },
+            }),
+        }
+    }
+
+    pub fn save_tile_map(tiles: &Vec<&ChessPiece>) -> String {
+        let color_mappings = PieceParser::reverse_color_mappings();
+        let type_mappings = PieceParser::reverse_type_mappings();
+        let mut tile_map_builder = Vec::new();
+        for j in CellPosition::cell_range() {
+            let mut line_builder = String::new();
+            line_builder.push('|');
+            for i in CellPosition::cell_range() {
+                let symbol = match tiles.iter().find(|cp| cp.pos.i == i && cp.pos.j == j) {
+                    Some(piece) => format!(
+                        "{}_{}",
+                        color_mappings.get(&piece.color).unwrap(),
+                        type_mappings.get(&piece.piece_type).unwrap()
+                    ),
+                    None => "none".to_string(),
+                };
+                line_builder.push_str(symbol.as_str());
+                line_builder.push('|');
+            }
+            line_builder.push('\n');
+            tile_map_builder.push(line_builder)
+        }
+        let result: Vec<String> = tile_map_builder
+            .iter()
+            .rev()
+            .map(|s| s.to_owned())
+            .collect();
+        result.join("")
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use super::*;
+    use crate::fen_parser::{FenParser, START_POSITION_FEN};
+
+    fn parse_error(map: String) -> Option<PieceParseError> {
+        PieceParser::parse_tile_map(map).err()
+    }
+
+    fn replace_row(map: &str, row: usize, new_row: &str) -> String {
+        let mut lines: Vec<String> = map
+            .split('\n')
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.trim().to_string())
+            .collect();
+        lines[row] = new_row.to_string();
+        lines.join("\n")
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let result = PieceParser::parse_tile_map(PieceParser::default_tile_map()).unwrap();
+        assert_eq!(64, result.len());
+        for p in &result[0..16] {
+            assert!(p.is_some())
+        }
+        for p in &result[16..48] {
+            assert!(p.is_none())
+        }
+        for p in &result[48..64] {
+            assert!(p.is_some())
+        }
+        assert!(PieceParser::parse_tile_map(PieceParser::test_tile_map()).is_ok());
+    }
+
+    #[test]
+    fn test_default_map_is_start_position() {
+        let pieces: Vec<ChessPiece> = PieceParser::parse_tile_map(PieceParser::default_tile_map())
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        let (start_pieces, _) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+        let start_pieces: Vec<&ChessPiece> = start_pieces.iter().collect();
+        assert_eq!(
+            PieceParser::save_tile_map(&start_pieces),
+            PieceParser::save_tile_map(&pieces)
+        );
+    }
+
+    #[test]
+    fn test_parse_piece() {
+        let result = PieceParser::parse_piece("w_bi", 0, 1).unwrap();
+        assert!(result.is_some());
+        let chess_piece = result.unwrap();
+
+        assert_eq!(PieceType::BISHOP, chess_piece.piece_type);
+        assert_eq!(ChessColor::WHITE, chess_piece.color);
+        assert_eq!(CellPosition { i: 0, j: 1 }, chess_piece.pos);
+    }
+
+    #[test]
+    fn test_parse_map_shape_errors() {
+        let map = PieceParser::default_tile_map();
+        let lines: Vec<&str> = map.split('\n').filter(|l| !l.trim().is_empty()).collect();
+        assert_eq!(
+            Some(PieceParseError::WrongRowCount(7)),
+            parse_error(lines[1..].join("\n"))
+        );
+        assert_eq!(
+            Some(PieceParseError::WrongColumnCount { rank: 6, count: 7 }),
+            parse_error(replace_row(&map, 2, "|none|none|none|none|none|none|none|"))
+        );
+        assert_eq!(
+            Some(PieceParseError::UnknownSymbol {
+                symbol: "w_xx".to_string(),
+                cell: CellPosition { i: 2, j: 3 }
+            }),
+            parse_error(replace_row(
+                &map,
+                4,
+                "|none|none|w_xx|none|none|none|none|none|"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_map_position_errors() {
+        let map = PieceParser::default_tile_map();
+        assert_eq!(
+            Some(PieceParseError::WrongKingCount {
+                color: ChessColor::BLACK,
+                count: 0
+            }),
+            parse_error(replace_row(
+                &map,
+                0,
+                "|b_ro|b_kn|b_bi|b_qu|none|b_bi|b_kn|b_ro|"
+            ))
+        );
+        assert_eq!(
+            Some(PieceParseError::WrongKingCount {
+                color: ChessColor::WHITE,
+                count: 2
+            }),
+            parse_error(replace_row(
+                &map,
+                4,
+                "|none|none|none|none|w_ki|none|none|none|"
+            ))
+        );
+        assert_eq!(
+            Some(PieceParseError::PawnOnBackRank(CellPosition { i: 0, j: 7 })),
+            parse_error(replace_row(
+                &map,
+                0,
+                "|b_pa|b_kn|b_bi|b_qu|b_ki|b_bi|b_kn|b_ro|"
+            ))
+        );
+        // the e-pawn is gone and the white rook gives check with white to move
+        let check_map = replace_row(
+            &replace_row(&map, 1, "|b_pa|b_pa|b_pa|b_pa|none|b_pa|b_pa|b_pa|"),
+            6,
+            "|w_pa|w_pa|w_pa|w_pa|none|w_pa|w_pa|w_pa|",
+        );
+        let check_map = replace_row(&check_map, 3, "|none|none|none|none|w_ro|none|none|none|");
+        let check_map = replace_row(&check_map, 7, "|none|w_kn|w_bi|w_qu|w_ki|w_bi|w_kn|w_ro|");
+        assert_eq!(
+            Some(PieceParseError::SideNotToMoveInCheck(ChessColor::BLACK)),
+            parse_error(check_map)
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    io::{BufRead, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{
+    ai_engine::AiEngine,
+    chess_move_state::MoveState,
+    chess_piece::ChessPiece,
+    common_chess::ChessColor,
+    fen_parser::{FenParser, START_POSITION_FEN},
+    notation::Notation,
+};
+
+const ENGINE_NAME: &str = "chess-bevy-game";
+const ENGINE_AUTHOR: &str = "chess-bevy-game developers";
+const MAX_DEPTH: u32 = 64;
+// think time of "go infinite" and "go depth", the search runs until stop or the depth
+const INFINITE_THINK_TIME: Duration = Duration::from_secs(24 * 60 * 60);
+// moves the remaining clock time is shared between when the GUI gives no movestogo
+const DEFAULT_MOVES_TO_GO: u64 = 30;
+// kept on the clock for the GUI and pipe latency
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+
+struct SearchThread {
+    handle: JoinHandle<()>,
+    is_cancelled: Arc<AtomicBool>,
+    // "go infinite" or a plain "go", it only ends with stop
+    is_infinite: bool,
+}
+
+// UCI protocol front end of the engine, commands are read line by line
+// and the search runs on its own thread so that "stop" is heard while thinking
+pub struct Uci<W: Write + Send + 'static> {
+    output: Arc<Mutex<W>>,
+    pieces: Vec<ChessPiece>,
+    move_state: MoveState,
+    search: Option<SearchThread>,
+}
+
+impl<W: Write + Send + 'static> Uci<W> {
+    pub fn new(output: W) -> Uci<W> {
+        let (pieces, move_state) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        Uci {
+            output: Arc::new(Mutex::new(output)),
+            pieces,
+            move_state,
+            search: None,
+        }
+    }
+
+    // handles commands until "quit" or the end of the input, a search is stopped on quit
+    // while at the end of a scripted input a limited one is waited for
+    pub fn run(&mut self, input: impl BufRead) {
+        for line in input.lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if !self.handle_command(&line) {
+                self.stop_search();
+                return;
+            }
+        }
+        self.wait_search();
+    }
+
+    // false once the engine has to quit
+    fn handle_command(&mut self, line: &str) -> bool {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = tokens.split_first() else {
+            return true;
+        };
+        match *command {
+            "uci" => {
+                self.write_line(&format!("id name {}", ENGINE_NAME));
+                self.write_line(&format!("id author {}", ENGINE_AUTHOR));
+                self.write_line("uciok");
+            }
+            "isready" => self.write_line("readyok"),
+            "ucinewgame" => {
+                self.stop_search();
+                self.set_position(&["startpos"]);
+            }
+            "position" => {
+                self.stop_search();
+                self.set_position(args);
+            }
+            "go" => {
+                self.stop_search();
+                self.start_search(args);
+            }
+            "stop" => self.stop_search(),
+            "quit" => return false,
+            // unknown commands are ignored by the protocol
+            _ => {}
+        }
+        true
+    }
+
+    // "startpos [moves ...]" or "fen <fen> [moves ...]"
+    fn set_position(&mut self, args: &[&str]) {
+        let moves_index = args
+            .iter()
+            .position(|arg| *arg == "moves")
+            .unwrap_or(args.len());
+        let fen = match args.first() {
+            Some(&"startpos") => START_POSITION_FEN.to_string(),
+            Some(&"fen") => args[1..moves_index].join(" "),
+            _ => {
+                self.write_line("info string expected startpos or fen");
+                return;
+            }
+        };
+        let (pieces, move_state) = match FenParser::parse_fen(&fen) {
+            Ok(position) => position,
+            Err(err) => {
+                self.write_line(&format!("info string invalid fen '{}': {}", fen, err));
+                return;
+            }
+        };
+        self.pieces = pieces;
+        self.move_state = move_state;
+
+        for uci_move in args.iter().skip(moves_index + 1) {
+            let chess_move = Notation::move_from_uci(uci_move).and_then(|(from, to, promotion)| {
+                self.move_state
+                    .make_move(&mut self.pieces, &from, &to, promotion)
+            });
+            if chess_move.is_none() || self.move_state.promotion_cell.is_some() {
+                self.write_line(&format!("info string illegal move {}", uci_move));
+                return;
+            }
+            self.move_state.next_move();
+        }
+    }
+
+    fn start_search(&mut self, args: &[&str]) {
+        let (max_depth, think_time, is_infinite) = self.search_limits(args);
+        let pieces = self.pieces.clone();
+        let move_state = self.move_state.clone();
+        let output = self.output.clone();
+        let is_cancelled = Arc::new(AtomicBool::new(false));
+        let is_search_cancelled = is_cancelled.clone();
+
+        let handle = thread::spawn(move || {
+            let best_move = AiEngine::best_move(
+                &pieces,
+                &move_state,
+                max_depth,
+                think_time,
+                &is_search_cancelled,
+            );
+            let best_move = best_move
+                .map(|engine_move| {
+                    Notation::move_to_uci(
+                        &engine_move.from,
+                        &engine_move.to,
+                        &engine_move.promotion,
+                    )
+                })
+                .unwrap_or("0000".to_string());
+            Uci::write_to(&output, &format!("bestmove {}", best_move));
+        });
+        self.search = Some(SearchThread {
+            handle,
+            is_cancelled,
+            is_infinite,
+        });
+    }
+
+    // depth and time of "go depth", "go movetime" and "go wtime .. btime .. winc .. binc ..",
+    // anything else searches until stop, which is the only infinite search
+    fn search_limits(&self, args: &[&str]) -> (u32, Duration, bool) {
+        let value = |name: &str| -> Option<u64> {
+            args.iter()
+                .position(|arg| *arg == name)
+                .and_then(|index| args.get(index + 1))
+                .and_then(|value| value.parse().ok())
+        };
+        let (time, increment) = match self.move_state.current_collor {
+            ChessColor::WHITE => (value("wtime"), value("winc")),
+            ChessColor::BLACK => (value("btime"), value("binc")),
+        };
+
+        if let Some(depth) = value("depth") {
+            (
+                depth.clamp(1, MAX_DEPTH as u64) as u32,
+                INFINITE_THINK_TIME,
+                false,
+            )
+        } else if let Some(movetime) = value("movetime") {
+            (MAX_DEPTH, Duration::from_millis(movetime), false)
+        } else if let Some(time) = time {
+            let moves_to_go = value("movestogo").unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+            let time = Duration::from_millis(time);
+            let think_time =
+                time / moves_to_go as u32 + Duration::from_millis(increment.unwrap_or(0)) / 2;
+            (
+                MAX_DEPTH,
+                think_time.min(time.saturating_sub(MOVE_OVERHEAD)),
+                false,
+            )
+        } else {
+            (MAX_DEPTH, INFINITE_THINK_TIME, true)
+        }
+    }
+
+    // the cancelled search still writes the best move found so far
+    fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            search.is_cancelled.store(true, Ordering::Relaxed);
+            let _ = search.handle.join();
+        }
+    }
+
+    fn wait_search(&mut self) {
+        if self
+            .search
+            .as_ref()
+            .is_some_and(|search| search.is_infinite)
+        {
+            self.stop_search();
+        } else if let Some(search) = self.search.take() {
+            let _ = search.handle.join();
+        }
+    }
+
+    fn write_line(&self, line: &str) {
+        Uci::write_to(&self.output, line);
+    }
+
+    fn write_to(output: &Mutex<W>, line: &str) {
+        if let Ok(mut output) = output.lock() {
+            let _ = writeln!(output, "{}", line).and_then(|_| output.flush());
+        }
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    // output lines of the engine for a scripted input
+    fn run_script(script: &str) -> Vec<String> {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let mut uci = Uci::new(SharedOutput(output.clone()));
+        uci.run(Cursor::new(script.to_string()));
+        let output = output.lock().unwrap();
+        String::from_utf8(output.clone())
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_uci_handshake() {
+        let output = run_script("uci\nisready\nquit\n");
+        assert_eq!(
+            vec![
+                format!("id name {}", ENGINE_NAME),
+                format!("id author {}", ENGINE_AUTHOR),
+                "uciok".to_string(),
+                "readyok".to_string(),
+            ],
+            output
+        );
+    }
+
+    #[test]
+    fn test_uci_position_with_moves() {
+        let mut uci = Uci::new(Vec::new());
+        uci.handle_command("position startpos moves e2e4 c7c5 g1f3");
+        assert_eq!(
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+            FenParser::save_fen(&uci.pieces.iter().collect(), &uci.move_state)
+        );
+
+        uci.handle_command("position fen 4k3/P7/8/8/8/8/8/4K3 w - - 0 1 moves a7a8q e8d7");
+        assert_eq!(
+            "Q7/3k4/8/8/8/8/8/4K3 w - - 1 2",
+            FenParser::save_fen(&uci.pieces.iter().collect(), &uci.move_state)
+        );
+    }
+
+    #[test]
+    fn test_uci_illegal_move() {
+        let output = run_script("position startpos moves e2e4 e2e4\nquit\n");
+        assert_eq!(vec!["info string illegal move e2e4".to_string()], output);
+    }
+
+    #[test]
+    fn test_uci_go_depth_finds_mate() {
+        let output = run_script("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1\ngo depth 2\n");
+        assert_eq!(vec!["bestmove a1a8".to_string()], output);
+    }
+
+    #[test]
+    fn test_uci_stop_infinite_search() {
+        let output = run_script("position startpos\ngo infinite\nisready\nstop\nquit\n");
+        assert_eq!("readyok", output[0]);
+        assert!(output[1].starts_with("bestmove "));
+        assert_eq!(2, output.len());
+    }
+
+    #[test]
+    fn test_uci_quit_stops_search() {
+        let output = run_script("position startpos\ngo depth 60\nquit\n");
+        assert_eq!(1, output.len());
+        assert!(output[0].starts_with("bestmove "));
+    }
+
+    #[test]
+    fn test_uci_no_legal_moves() {
+        let output = run_script("position fen 7k/5QQ1/8/8/8/8/8/K7 b - - 0 1\ngo movetime 10\n");
+        assert_eq!(vec!["bestmove 0000".to_string()], output);
+    }
+
+    #[test]
+    fn test_uci_clock_limits() {
+        let uci = Uci::new(Vec::new());
+        assert_eq!(
+            (MAX_DEPTH, Duration::from_millis(2_000 + 500), false),
+            uci.search_limits(&[
+                "wtime",
+                "60000",
+                "btime",
+                "1000",
+                "winc",
+                "1000",
+                "movestogo",
+                "30"
+            ])
+        );
+        assert_eq!(
+            (3, INFINITE_THINK_TIME, false),
+            uci.search_limits(&["depth", "3"])
+        );
+        assert_eq!(
+            (MAX_DEPTH, INFINITE_THINK_TIME, false),
+            uci.search_limits(&["depth", "64"])
+        );
+        assert_eq!(
+            (MAX_DEPTH, Duration::from_millis(250), false),
+            uci.search_limits(&["movetime", "250"])
+        );
+        assert_eq!(
+            (MAX_DEPTH, INFINITE_THINK_TIME, true),
+            uci.search_limits(&[])
+        );
+        assert_eq!(
+            (MAX_DEPTH, INFINITE_THINK_TIME, true),
+            uci.search_limits(&["infinite"])
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::Mutex,
+    time::Duration,
+};
+
+use crate::{
+    ai_engine::EngineMove, chess_move_state::MoveState, chess_piece::ChessPiece,
+    fen_parser::FenParser, notation::Notation,
+};
+
+#[derive(Debug)]
+pub enum UciClientError {
+    Io(io::Error),
+    EngineExited,
+    InvalidBestMove(String),
+}
+
+impl fmt::Display for UciClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UciClientError::Io(err) => write!(f, "engine process i/o failed: {}", err),
+            UciClientError::EngineExited => write!(f, "engine process closed its output"),
+            UciClientError::InvalidBestMove(line) => write!(f, "invalid engine reply '{}'", line),
+        }
+    }
+}
+
+impl std::error::Error for UciClientError {}
+
+impl From<io::Error> for UciClientError {
+    fn from(err: io::Error) -> Self {
+        UciClientError::Io(err)
+    }
+}
+
+// external UCI engine running as a child process,
+// a search holds the output while "stop" can still be written from another thread
+pub struct UciEngineProcess {
+    child: Mutex<Child>,
+    stdin: Mutex<ChildStdin>,
+    stdout: Mutex<BufReader<ChildStdout>>,
+}
+
+impl UciEngineProcess {
+    // starts the executable and waits until it is ready for a game
+    pub fn launch(path: &Path) -> Result<UciEngineProcess, UciClientError> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(UciClientError::EngineExited);
+        };
+        let engine = UciEngineProcess {
+            child: Mutex::new(child),
+            stdin: Mutex::new(stdin),
+            stdout: Mutex::new(BufReader::new(stdout)),
+        };
+
+        let mut stdout = engine.lock_stdout();
+        engine.send("uci")?;
+        UciEngineProcess::read_until(&mut stdout, |line| line == "uciok")?;
+        engine.send("isready")?;
+        UciEngineProcess::read_until(&mut stdout, |line| line == "readyok")?;
+        drop(stdout);
+        Ok(engine)
+    }
+
+    // engine's move for the position, None when it has no legal move
+    pub fn best_move(
+        &self,
+        pieces: &[ChessPiece],
+        move_state: &MoveState,
+        think_time: Duration,
+    ) -> Result<Option<EngineMove>, UciClientError> {
+        let mut stdout = self.lock_stdout();
+        self.send(&UciEngineProcess::position_command(pieces, move_state))?;
+        self.send(&format!("go movetime {}", think_time.as_millis()))?;
+        let line = UciEngineProcess::read_until(&mut stdout, |line| {
+            line.split_whitespace().next() == Some("bestmove")
+        })?;
+
+        let best_move = line.split_whitespace().nth(1).unwrap_or_default();
+        if best_move == "0000" || best_move == "(none)" {
+            return Ok(None);
+        }
+        Notation::move_from_uci(best_move)
+            .map(|(from, to, promotion)| {
+                Some(EngineMove {
+                    from,
+                    to,
+                    promotion,
+                })
+            })
+            .ok_or(UciClientError::InvalidBestMove(line))
+    }
+
+    // asks a running search for its move right away
+    pub fn stop(&self) {
+        if let Err(err) = self.send("stop") {
+            log::warn!("failed to stop the engine: {}", err);
+        }
+    }
+
+    // the moves from the start position are sent so that the engine sees repetitions
+    pub fn position_command(pieces: &[ChessPiece], move_state: &MoveState) -> String {
+        match &move_state.start_fen {
+            Some(start_fen) if !move_state.move_history.is_empty() => {
+                let moves: Vec<String> = move_state
+                    .move_history
+                    .iter()
+                    .map(|chess_move| {
+                        Notation::move_to_uci(
+                            &chess_move.piece.pos,
+                            &chess_move.to,
+                            &chess_move.promotion,
+                        )
+                    })
+                    .collect();
+                format!("position fen {} moves {}", start_fen, moves.join(" "))
+            }
+            _ => {
+                let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+                format!("position fen {}", FenParser::save_fen(&pieces, move_state))
+            }
+        }
+    }
+
+    fn send(&self, command: &str) -> Result<(), UciClientError> {
+        let mut stdin = self.stdin.lock().unwrap_or_else(|err| err.into_inner());
+        writeln!(stdin, "{}", command)?;
+        stdin.flush()?;
+        Ok(())
+    }
+
+    fn lock_stdout(&self) -> std::sync::MutexGuard<'_, BufReader<ChildStdout>> {
+        self.stdout.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // skips info and other lines up to the expected one
+    fn read_until(
+        stdout: &mut BufReader<ChildStdout>,
+        is_expected: impl Fn(&str) -> bool,
+    ) -> Result<String, UciClientError> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if stdout.read_line(&mut line)? == 0 {
+                return Err(UciClientError::EngineExited);
+            }
+            let line = line.trim();
+            if is_expected(line) {
+                return Ok(line.to_string());
+            }
+        }
+    }
+}
+
+impl Drop for UciEngineProcess {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let mut child = self.child.lock().unwrap_or_else(|err| err.into_inner());
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod run_tests {
+    use std::{fs, os::unix::fs::PermissionsExt, path::PathBuf};
+
+    use super::*;
+    use crate::{chess_piece::PieceType, fen_parser::START_POSITION_FEN};
+
+    // shell script answering the handshake and every search with the given move
+    fn stub_engine(name: &str, best_move: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "chess-engine-stub-{}-{}.sh",
+            name,
+            std::process::id()
+        ));
+        let script = format!(
+            "#!/bin/sh\n\
+             while read -r line; do\n\
+             case \"$line\" in\n\
+             uci) echo 'id name stub'; echo 'uciok' ;;\n\
+             isready) echo 'readyok' ;;\n\
+             go*) echo 'info depth 1 score cp 0'; echo 'bestmove {}' ;;\n\
+             quit) exit 0 ;;\n\
+             esac\n\
+             done\n",
+            best_move
+        );
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_uci_client_best_move() {
+        let path = stub_engine("best-move", "e7e8q");
+        let engine = UciEngineProcess::launch(&path).unwrap();
+        let (pieces, move_state) = FenParser::parse_fen("7k/4P3/8/8/8/8/8/K7 w - - 0 1").unwrap();
+        let best_move = engine
+            .best_move(&pieces, &move_state, Duration::from_millis(10))
+            .unwrap();
+        assert_eq!(
+            Some(EngineMove {
+                from: Notation::cell_from_string("e7").unwrap(),
+                to: Notation::cell_from_string("e8").unwrap(),
+                promotion: Some(PieceType::QUEEN),
+            }),
+            best_move
+        );
+        drop(engine);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_uci_client_no_move() {
+        let path = stub_engine("no-move", "0000");
+        let engine = UciEngineProcess::launch(&path).unwrap();
+        let (pieces, move_state) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        let best_move = engine.best_move(&pieces, &move_state, Duration::from_millis(10));
+        assert_eq!(None, best_move.unwrap());
+        drop(engine);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_uci_client_missing_executable() {
+        let path = std::env::temp_dir().join("chess-engine-no-such-engine");
+        assert!(matches!(
+            UciEngineProcess::launch(&path),
+            Err(UciClientError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_uci_client_position_command() {
+        let (mut pieces, mut move_state) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        assert_eq!(
+            format!("position fen {}", START_POSITION_FEN),
+            UciEngineProcess::position_command(&pieces, &move_state)
+        );
+
+        for (from, to) in [("e2", "e4"), ("e7", "e5")] {
+            let from = Notation::cell_from_string(from).unwrap();
+            let to = Notation::cell_from_string(to).unwrap();
+            move_state.make_move(&mut pieces, &from, &to, None).unwrap();
+            move_state.next_move();
+        }
+        assert_eq!(
+            format!("position fen {} moves e2e4 e7e5", START_POSITION_FEN),
+            UciEngineProcess::position_command(&pieces, &move_state)
+        );
+    }
+}
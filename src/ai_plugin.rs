@@ -0,0 +1,264 @@
+use std::{
+    env,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use chess_engine::{
+    ai_engine::{AiEngine, EngineMove},
+    chess_piece::ChessPiece,
+    notation::Notation,
+    uci_client::{UciClientError, UciEngineProcess},
+};
+use futures_lite::future;
+
+use crate::{
+    chess_board_plugin::start_made_move_animation,
+    models::{
+        app_state::{AppState, PromotionState},
+        board_piece::BoardPiece,
+        chess_cell::ChessCell,
+        common_resources::{GameMode, Opponent},
+        game_move_state::GameMoveState,
+        removed_chess_piece::ChessPieceRemovedEvent,
+    },
+};
+
+const AI_MAX_DEPTH: u32 = 6;
+const AI_THINK_TIME: Duration = Duration::from_secs(3);
+
+// search of the computer's move running on the async compute pool
+#[derive(Default, Resource)]
+pub struct ComputerTask {
+    task: Option<Task<Option<EngineMove>>>,
+    // ply the search was started for, the result is dropped once the game moved on
+    ply_count: u32,
+    is_cancelled: Arc<AtomicBool>,
+    // external engine the search was sent to
+    engine: Option<Arc<UciEngineProcess>>,
+}
+
+impl ComputerTask {
+    pub fn is_thinking(&self) -> bool {
+        self.task.is_some()
+    }
+
+    // a running search ignores a dropped task, so it is told to stop as well
+    fn cancel(&mut self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+        if let Some(engine) = self.engine.take() {
+            engine.stop();
+        }
+        self.task = None;
+    }
+}
+
+// running external engine of Opponent::UciEngine, kept between games
+#[derive(Default, Resource)]
+pub struct UciOpponent {
+    engine: Option<Arc<UciEngineProcess>>,
+    // start and handshake of the executable, the built-in engine plays until it is done
+    launch: Option<Task<Result<UciEngineProcess, UciClientError>>>,
+}
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ComputerTask::default())
+            .insert_resource(UciOpponent::default())
+            .add_startup_system(engine_from_args)
+            .add_system(launch_uci_opponent.in_schedule(OnEnter(AppState::Game)))
+            .add_system(cancel_computer_move.in_schedule(OnExit(AppState::Game)))
+            .add_systems(
+                (
+                    poll_uci_launch_system,
+                    start_computer_move_system,
+                    poll_computer_move_system,
+                )
+                    .chain()
+                    .distributive_run_if(in_state(PromotionState::Inactive))
+                    .in_set(OnUpdate(AppState::Game)),
+            );
+    }
+}
+
+// `--engine <path>` plays the computer side with a UCI engine executable
+fn engine_from_args(mut game_mode: ResMut<GameMode>) {
+    let args: Vec<String> = env::args().collect();
+    if let Some(path) = args
+        .iter()
+        .position(|arg| arg == "--engine")
+        .and_then(|index| args.get(index + 1))
+    {
+        game_mode.opponent = Opponent::UciEngine(PathBuf::from(path));
+    }
+}
+
+// the handshake runs on the async compute pool, an executable that never answers
+// leaves the game to the built-in engine instead of freezing it
+fn launch_uci_opponent(game_mode: Res<GameMode>, mut uci_opponent: ResMut<UciOpponent>) {
+    let Opponent::UciEngine(path) = &game_mode.opponent else {
+        return;
+    };
+    if game_mode.computer_color.is_none()
+        || uci_opponent.engine.is_some()
+        || uci_opponent.launch.is_some()
+    {
+        return;
+    }
+    let path = path.clone();
+    uci_opponent.launch =
+        Some(AsyncComputeTaskPool::get().spawn(async move { UciEngineProcess::launch(&path) }));
+}
+
+// the built-in engine keeps playing when the executable does not start
+fn poll_uci_launch_system(mut uci_opponent: ResMut<UciOpponent>) {
+    let Some(launch) = uci_opponent.launch.as_mut() else {
+        return;
+    };
+    let Some(result) = future::block_on(future::poll_once(launch)) else {
+        return;
+    };
+    uci_opponent.launch = None;
+    match result {
+        Ok(engine) => uci_opponent.engine = Some(Arc::new(engine)),
+        Err(err) => warn!("failed to launch the engine: {}", err),
+    }
+}
+
+fn cancel_computer_move(mut computer_task: ResMut<ComputerTask>) {
+    computer_task.cancel();
+}
+
+// starts thinking on a snapshot of the game once the computer's turn comes,
+// it waits while the player steps through undone moves
+fn start_computer_move_system(
+    game_mode: Res<GameMode>,
+    move_state: Res<GameMoveState>,
+    uci_opponent: Res<UciOpponent>,
+    q_chess_piece: Query<&BoardPiece>,
+    mut computer_task: ResMut<ComputerTask>,
+) {
+    if computer_task.is_thinking() {
+        return;
+    }
+    if game_mode.computer_color.as_ref() != Some(&move_state.current_collor) {
+        return;
+    }
+    if move_state.move_in_action
+        || move_state.is_game_over()
+        || move_state.promotion_cell.is_some()
+        || !move_state.redo_history.is_empty()
+    {
+        return;
+    }
+
+    let pieces: Vec<ChessPiece> = q_chess_piece.iter().map(|cp| cp.0.clone()).collect();
+    let move_state_snapshot = move_state.rules.clone();
+    let is_cancelled = Arc::new(AtomicBool::new(false));
+    let is_task_cancelled = is_cancelled.clone();
+    let engine = match game_mode.opponent {
+        Opponent::UciEngine(_) => uci_opponent.engine.clone(),
+        Opponent::BuiltIn => None,
+    };
+    let task_engine = engine.clone();
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        if let Some(engine) = task_engine {
+            match engine.best_move(&pieces, &move_state_snapshot, AI_THINK_TIME) {
+                // a pawn move to the last rank has to name its promotion piece
+                Ok(Some(engine_move))
+                    if !move_state_snapshot.is_complete_move(
+                        &pieces,
+                        &engine_move.from,
+                        &engine_move.to,
+                        &engine_move.promotion,
+                    ) =>
+                {
+                    warn!(
+                        "engine move {} is not legal, the built-in one moves instead",
+                        Notation::move_to_uci(
+                            &engine_move.from,
+                            &engine_move.to,
+                            &engine_move.promotion
+                        )
+                    )
+                }
+                Ok(engine_move) => return engine_move,
+                Err(err) => warn!("engine failed, the built-in one moves instead: {}", err),
+            }
+        }
+        AiEngine::best_move(
+            &pieces,
+            &move_state_snapshot,
+            AI_MAX_DEPTH,
+            AI_THINK_TIME,
+            &is_task_cancelled,
+        )
+    });
+
+    *computer_task = ComputerTask {
+        task: Some(task),
+        ply_count: move_state.ply_count,
+        is_cancelled,
+        engine,
+    };
+}
+
+// makes the move found by the finished search the same way a player's move is made
+fn poll_computer_move_system(
+    game_mode: Res<GameMode>,
+    assets: Res<AssetServer>,
+    mut move_state: ResMut<GameMoveState>,
+    mut computer_task: ResMut<ComputerTask>,
+    q_chess_cell: Query<(Entity, &ChessCell)>,
+    mut q_chess_piece: Query<(Entity, &mut BoardPiece, &mut Handle<Image>)>,
+    mut piece_taken_event_writer: EventWriter<ChessPieceRemovedEvent>,
+) {
+    let is_outdated = computer_task.ply_count != move_state.ply_count
+        || move_state.is_game_over()
+        || game_mode.computer_color.as_ref() != Some(&move_state.current_collor);
+    let Some(task) = computer_task.task.as_mut() else {
+        return;
+    };
+    if is_outdated {
+        computer_task.cancel();
+        return;
+    }
+    let Some(engine_move) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    computer_task.task = None;
+    let Some(engine_move) = engine_move else {
+        return;
+    };
+
+    let mut pieces: Vec<ChessPiece> = q_chess_piece
+        .iter()
+        .map(|(_, cp, _)| cp.0.clone())
+        .collect();
+    let Some(chess_move) = move_state.make_move(
+        &mut pieces,
+        &engine_move.from,
+        &engine_move.to,
+        engine_move.promotion,
+    ) else {
+        return;
+    };
+    start_made_move_animation(
+        &mut move_state,
+        chess_move,
+        &q_chess_cell,
+        &mut q_chess_piece,
+        &assets,
+        &mut piece_taken_event_writer,
+    );
+}
@@ -1,10 +1,11 @@
+use crate::models::board_piece::BoardPiece;
 use crate::models::chess_cell::ChessCell;
-use crate::models::chess_piece::{ChessPiece, PieceType};
-use crate::models::common_chess::ChessColor;
 use crate::models::common_resources::{DiscardArea, FontHolder, StaticDespawnable};
 use crate::models::removed_chess_piece::RemovedChessPiece;
 use crate::Board;
 use bevy::prelude::*;
+use chess_engine::chess_piece::{ChessPiece, PieceType};
+use chess_engine::common_chess::ChessColor;
 
 pub struct AssetsHelper;
 
@@ -29,7 +30,7 @@ impl AssetsHelper {
                 },
                 ..Default::default()
             })
-            .insert(chess_piece);
+            .insert(BoardPiece(chess_piece));
     }
 
     pub fn spawn_removed_piece(
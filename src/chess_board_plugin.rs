@@ -5,33 +5,53 @@ use bevy::{
     render::{mesh::Indices, render_resource::PrimitiveTopology},
     sprite::MaterialMesh2dBundle,
 };
+use chess_engine::{
+    chess_move::ChessMove,
+    chess_move_state::MoveState,
+    chess_piece::ChessPiece,
+    common_chess::{CellPosition, FIRST_ELEMENT, LAST_ELEMENT},
+    fen_parser::FenParser,
+    notation::Notation,
+    piece_parser::PieceParser,
+};
 
 use crate::{
     assets_helper::AssetsHelper,
     models::{
-        app_state::AppState,
+        app_state::{AppState, BrowseState, PromotionState},
+        board_piece::BoardPiece,
         chess_cell::{ChessCell, ChessCellState},
-        chess_move_state::MoveState,
-        chess_piece::ChessPiece,
-        common_resources::{Board, BoardPointer, FontHolder, GameState, StaticDespawnable},
+        common_resources::{
+            Board, BoardPointer, BrowsedPosition, FontHolder, GameMode, GameState, LoadedGame,
+            StaticDespawnable,
+        },
+        game_move_state::GameMoveState,
         removed_chess_piece::ChessPieceRemovedEvent,
     },
-    piece_parser::PieceParser,
 };
 
+// FEN of the position left when going back to the main menu
 #[derive(Default, Resource)]
-struct PiecesStore {
-    state: Option<String>,
+pub struct PiecesStore {
+    pub state: Option<String>,
 }
 #[derive(Default, Resource)]
 struct MoveStateStore {
     state: Option<MoveState>,
 }
+// piece picked up by a press and carried by the pointer until the button is released
+#[derive(Default, Resource)]
+struct PieceDrag {
+    piece: Option<Entity>,
+    // a press on the selected piece without dragging it away drops the selection
+    was_selected: bool,
+}
 
 const BOARDING_Z: f32 = 0.0;
 const BOARD_Z: f32 = 1.0;
-const PIECES_Z: f32 = 3.0;
+pub const PIECES_Z: f32 = 3.0;
 const TEXT_Z: f32 = 2.0;
+const DRAGGED_PIECE_Z: f32 = 4.0;
 
 pub struct ChessBoardPlugin;
 
@@ -39,6 +59,7 @@ impl Plugin for ChessBoardPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(PiecesStore::default())
             .insert_resource(MoveStateStore::default())
+            .insert_resource(PieceDrag::default())
             .add_event::<ChessPieceRemovedEvent>()
             .add_systems(
                 (
@@ -51,7 +72,12 @@ impl Plugin for ChessBoardPlugin {
                     .in_schedule(OnEnter(AppState::Game)),
             )
             .add_systems(
-                (despawn_chess_pieces, despawn_static, save_move_state)
+                (
+                    despawn_chess_pieces,
+                    despawn_static,
+                    save_move_state,
+                    reset_piece_drag,
+                )
                     .in_schedule(OnExit(AppState::Game)),
             )
             .add_systems(
@@ -59,12 +85,18 @@ impl Plugin for ChessBoardPlugin {
                     highlight_chess_piece_system,
                     calculate_chess_cell_state_system,
                     draw_highlight_chess_cell_system,
-                    set_piece_selected,
-                    set_cell_selected,
                     remove_taken_piece_system,
                     move_piece_system,
+                    // a piece carried while the browsing starts is put back as well
+                    drop_piece_system,
                 )
                     .in_set(OnUpdate(AppState::Game)),
+            )
+            .add_systems(
+                (set_piece_selected, set_cell_selected, drag_piece_system)
+                    .distributive_run_if(in_state(PromotionState::Inactive))
+                    .distributive_run_if(in_state(BrowseState::Latest))
+                    .in_set(OnUpdate(AppState::Game)),
             );
     }
 }
@@ -72,22 +104,25 @@ impl Plugin for ChessBoardPlugin {
 fn set_up_resources(
     mut commands: Commands,
     game_state: Res<GameState>,
+    loaded_game: Res<LoadedGame>,
     mut move_state_store: ResMut<MoveStateStore>,
 ) {
-    if let GameState::NEW = *game_state {
-        move_state_store.state = None
+    match *game_state {
+        GameState::NEW => move_state_store.state = None,
+        GameState::CONTINUE => {}
+        GameState::LOAD => move_state_store.state = loaded_game.move_state.clone(),
     }
     let move_state = move_state_store
         .state
         .take()
         .unwrap_or(MoveState::default());
 
-    commands.insert_resource(move_state);
+    commands.insert_resource(GameMoveState::new(move_state));
 }
 
 fn set_up_chess_board_system(assets: Res<AssetServer>, mut commands: Commands, board: Res<Board>) {
-    for j in board.cell_range() {
-        for i in board.cell_range() {
+    for j in CellPosition::cell_range() {
+        for i in CellPosition::cell_range() {
             let cell = ChessCell::from(i, j);
             let (x, y) = board.coordinates(&cell.pos);
             AssetsHelper::spawn_chess_cell(
@@ -108,11 +143,11 @@ fn set_up_board_boarding_system(
     board: Res<Board>,
 ) {
     let text_offset = 1.3;
-    for j in board.cell_range() {
-        for (i, x_direction) in vec![(board.first_element, -1.), (board.last_element, 1.)] {
+    for j in CellPosition::cell_range() {
+        for (i, x_direction) in [(FIRST_ELEMENT, -1.), (LAST_ELEMENT, 1.)] {
             let y = board.y_coordinate(j);
             let x = board.x_coordinate(i) + (board.image_size_scaled() / text_offset) * x_direction;
-            let text = (j + 1).to_string();
+            let text = Notation::rank_char(j).to_string();
 
             AssetsHelper::spawn_text_boarding(
                 &mut commands,
@@ -123,13 +158,12 @@ fn set_up_board_boarding_system(
             );
         }
     }
-    let chars = vec!['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H'];
-    for i in board.cell_range() {
-        for (j, y_directtion) in vec![(board.first_element, -1.), (board.last_element, 1.)] {
+    for i in CellPosition::cell_range() {
+        for (j, y_directtion) in [(FIRST_ELEMENT, -1.), (LAST_ELEMENT, 1.)] {
             let y =
                 board.y_coordinate(j) + (board.image_size_scaled() / text_offset) * y_directtion;
             let x = board.x_coordinate(i);
-            let text = chars[i as usize].to_string();
+            let text = Notation::file_char(i).to_ascii_uppercase().to_string();
 
             AssetsHelper::spawn_text_boarding(
                 &mut commands,
@@ -141,8 +175,8 @@ fn set_up_board_boarding_system(
         }
     }
 
-    for j in board.first_element..(board.last_element + 2) {
-        for i in board.first_element..(board.last_element + 2) {
+    for j in FIRST_ELEMENT..(LAST_ELEMENT + 2) {
+        for i in FIRST_ELEMENT..(LAST_ELEMENT + 2) {
             let y = board.y_coordinate(j) - board.image_size_scaled() / 2.;
             let x = board.x_coordinate(i) - board.image_size_scaled() / 2.;
             AssetsHelper::spawn_chess_boarding_cell(
@@ -160,31 +194,45 @@ fn set_up_chess_pieces_system(
     mut commands: Commands,
     board: Res<Board>,
     game_state: Res<GameState>,
+    loaded_game: Res<LoadedGame>,
     mut pieces_store: ResMut<PiecesStore>,
 ) {
-    if let GameState::NEW = *game_state {
-        pieces_store.state = None
+    match *game_state {
+        GameState::NEW => pieces_store.state = None,
+        GameState::CONTINUE => {}
+        GameState::LOAD => pieces_store.state = loaded_game.fen.clone(),
     }
 
-    let map = pieces_store
-        .state
-        .take()
-        .unwrap_or(PieceParser::default_tile_map());
-
-    for element in PieceParser::parse_tile_map(map) {
-        if let Some(piece) = element {
-            let (x, y) = board.coordinates(&piece.pos);
-            AssetsHelper::spawn_piece(
-                commands.borrow_mut(),
-                piece,
-                Vec3::new(x, y, PIECES_Z),
-                &assets,
-                &board,
-            );
-        }
+    let pieces = match pieces_store.state.take() {
+        Some(fen) => FenParser::parse_fen(&fen)
+            .map(|(pieces, _)| pieces)
+            .unwrap_or_else(|err| {
+                warn!("failed to restore pieces from FEN '{}': {}", fen, err);
+                default_pieces()
+            }),
+        None => default_pieces(),
+    };
+
+    for piece in pieces {
+        let (x, y) = board.coordinates(&piece.pos);
+        AssetsHelper::spawn_piece(
+            commands.borrow_mut(),
+            piece,
+            Vec3::new(x, y, PIECES_Z),
+            &assets,
+            &board,
+        );
     }
 }
 
+fn default_pieces() -> Vec<ChessPiece> {
+    PieceParser::parse_tile_map(PieceParser::default_tile_map())
+        .expect("the standard setup is valid")
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
 fn despawn_static(mut commands: Commands, q_despawn: Query<Entity, With<StaticDespawnable>>) {
     for entity in q_despawn.iter() {
         commands.entity(entity).despawn();
@@ -193,28 +241,31 @@ fn despawn_static(mut commands: Commands, q_despawn: Query<Entity, With<StaticDe
 
 fn despawn_chess_pieces(
     mut commands: Commands,
-    q_despawn: Query<(Entity, &ChessPiece)>,
-    board: Res<Board>,
+    q_despawn: Query<(Entity, &BoardPiece)>,
+    move_state: Res<GameMoveState>,
     mut pieces_store: ResMut<PiecesStore>,
 ) {
-    let pieces: Vec<&ChessPiece> = q_despawn.iter().map(|tup| tup.1).collect();
+    let pieces: Vec<&ChessPiece> = q_despawn.iter().map(|tup| &tup.1 .0).collect();
 
-    let tile_map = PieceParser::save_tile_map(&pieces, &board);
-    // warn!("tile_map:\n{}", tile_map);
-    pieces_store.state = Some(tile_map);
+    debug!("tile_map:\n{}", PieceParser::save_tile_map(&pieces));
+    pieces_store.state = Some(FenParser::save_fen(&pieces, &move_state));
     for (entity, _) in q_despawn.iter() {
         commands.entity(entity).despawn();
     }
 }
 
-fn save_move_state(move_state: Res<MoveState>, mut move_state_store: ResMut<MoveStateStore>) {
-    move_state_store.state = Some(move_state.clone())
+fn save_move_state(move_state: Res<GameMoveState>, mut move_state_store: ResMut<MoveStateStore>) {
+    move_state_store.state = Some(move_state.rules.clone())
+}
+
+fn reset_piece_drag(mut piece_drag: ResMut<PieceDrag>) {
+    *piece_drag = PieceDrag::default();
 }
 
 fn highlight_chess_piece_system(
-    mut q_chess_piece: Query<(Entity, &mut Transform, &ChessPiece)>,
+    mut q_chess_piece: Query<(Entity, &mut Transform, &BoardPiece)>,
     board_pointer: Res<BoardPointer>,
-    move_sate: Res<MoveState>,
+    move_sate: Res<GameMoveState>,
     board: Res<Board>,
 ) {
     if move_sate.move_in_action {
@@ -236,34 +287,61 @@ fn highlight_chess_piece_system(
 fn calculate_chess_cell_state_system(
     mut q_chess_cells: Query<&mut ChessCell>,
     board_pointer: Res<BoardPointer>,
-    move_state: Res<MoveState>,
-    q_chess_piece: Query<&ChessPiece>,
+    move_state: Res<GameMoveState>,
+    q_chess_piece: Query<&BoardPiece>,
     board: Res<Board>,
+    browsed_position: Option<Res<BrowsedPosition>>,
 ) {
-    if move_state.move_in_action {
+    if move_state.move_in_action && browsed_position.is_none() {
         return;
     }
-    if move_state.selected_piece.is_none() {
+
+    let pieces: Vec<&ChessPiece> = q_chess_piece.iter().map(|cp| &cp.0).collect();
+
+    // the last move and a checked king are shown under the selection highlights
+    let (last_move, checked_king) = match &browsed_position {
+        Some(browsed) => {
+            let browsed_pieces: Vec<&ChessPiece> = browsed.pieces.iter().collect();
+            (
+                browsed.move_state.last_move_cells(),
+                browsed.move_state.checked_king_cell(&browsed_pieces),
+            )
+        }
+        None => (
+            move_state.last_move_cells(),
+            move_state.checked_king_cell(&pieces),
+        ),
+    };
+    let base_state = |pos: &CellPosition| {
+        if checked_king.as_ref() == Some(pos) {
+            ChessCellState::IN_CHECK
+        } else if last_move.map(|(_, to)| to).as_ref() == Some(pos) {
+            ChessCellState::LAST_MOVE_TO
+        } else if last_move.map(|(from, _)| from).as_ref() == Some(pos) {
+            ChessCellState::LAST_MOVE_FROM
+        } else {
+            ChessCellState::NONE
+        }
+    };
+
+    // nothing is picked in a browsed position
+    let selected_piece = move_state
+        .selected_piece
+        .filter(|_| browsed_position.is_none())
+        .and_then(|entity| q_chess_piece.get(entity).ok());
+    let Some(selected_piece) = selected_piece else {
         for mut chess_cell in q_chess_cells.iter_mut() {
-            chess_cell.state = ChessCellState::NONE;
+            chess_cell.state = base_state(&chess_cell.pos);
         }
         return;
-    }
+    };
 
-    let selected_piece = q_chess_piece.get(move_state.selected_piece.unwrap()).ok();
-    if selected_piece.is_none() {
-        return;
-    }
-    let selected_piece = selected_piece.unwrap();
-
-    let pieces: Vec<&ChessPiece> = q_chess_piece.iter().collect();
-
-    let available_cells = selected_piece.get_available_cells_for_move(&board, &pieces);
+    let available_cells = selected_piece.get_legal_cells_for_move(&pieces, &move_state);
 
     for mut chess_cell in q_chess_cells.iter_mut() {
         let is_current_cell_selected = selected_piece.pos == chess_cell.pos;
         if is_current_cell_selected {
-            chess_cell.state = ChessCellState::NONE;
+            chess_cell.state = base_state(&chess_cell.pos);
             continue;
         }
 
@@ -281,7 +359,7 @@ fn calculate_chess_cell_state_system(
         } else if available_cells.contains(&chess_cell.pos) {
             chess_cell.state = ChessCellState::HIGHLIGHTED;
         } else {
-            chess_cell.state = ChessCellState::NONE;
+            chess_cell.state = base_state(&chess_cell.pos);
         }
     }
 }
@@ -319,31 +397,38 @@ fn draw_highlight_chess_cell_system(
                 Color::rgb(0.7, 1., 1.)
             }
             ChessCellState::ATTACKED => Color::ORANGE_RED,
+            ChessCellState::LAST_MOVE_FROM => Color::rgb(0.9, 0.9, 0.6),
+            ChessCellState::LAST_MOVE_TO => Color::rgb(0.8, 0.8, 0.45),
+            ChessCellState::IN_CHECK => Color::rgb(1., 0.45, 0.45),
         };
     }
 }
 
+// a press on an own piece selects it and picks it up for dragging
 fn set_piece_selected(
     buttons: Res<Input<MouseButton>>,
     pointer: Res<BoardPointer>,
     board: Res<Board>,
-    mut move_sate: ResMut<MoveState>,
-    q_chess_piece: Query<(Entity, &ChessPiece)>,
+    mut move_sate: ResMut<GameMoveState>,
+    mut piece_drag: ResMut<PieceDrag>,
+    game_mode: Res<GameMode>,
+    q_chess_piece: Query<(Entity, &BoardPiece)>,
 ) {
     if !buttons.just_pressed(MouseButton::Left) {
         return;
     }
-    if move_sate.move_in_action {
+    if move_sate.move_in_action || move_sate.is_game_over() {
+        return;
+    }
+    if !game_mode.is_local_side(&move_sate.current_collor) {
         return;
     }
 
     for (entity, piece) in q_chess_piece.iter() {
         if board.is_cell_matches(&piece.pos, &pointer) && move_sate.current_collor == piece.color {
-            if Some(entity) == move_sate.selected_piece {
-                move_sate.selected_piece = None
-            } else {
-                move_sate.selected_piece = Some(entity)
-            }
+            piece_drag.piece = Some(entity);
+            piece_drag.was_selected = Some(entity) == move_sate.selected_piece;
+            move_sate.selected_piece = Some(entity);
         }
     }
 }
@@ -352,12 +437,12 @@ fn set_cell_selected(
     buttons: Res<Input<MouseButton>>,
     pointer: Res<BoardPointer>,
     board: Res<Board>,
-    mut move_state: ResMut<MoveState>,
+    mut move_state: ResMut<GameMoveState>,
     q_chess_cell: Query<(Entity, &ChessCell)>,
-    q_chess_piece: Query<&ChessPiece>,
+    q_chess_piece: Query<(Entity, &BoardPiece)>,
     mut piece_taken_event_writer: EventWriter<ChessPieceRemovedEvent>,
 ) {
-    if move_state.move_in_action {
+    if move_state.move_in_action || move_state.is_game_over() {
         return;
     }
 
@@ -375,64 +460,213 @@ fn set_cell_selected(
     if selected_piece.is_none() {
         return;
     }
-    let selected_piece = selected_piece.unwrap();
-    let pieces: Vec<&ChessPiece> = q_chess_piece.iter().collect();
-
-    let available_cells = selected_piece.get_available_cells_for_move(&board, &pieces);
+    let (_, selected_piece) = selected_piece.unwrap();
 
     for (entity, cell) in q_chess_cell.iter() {
         let is_current_cell_selected = selected_piece.pos == cell.pos;
         if is_current_cell_selected {
             continue;
         }
-        if board.is_cell_matches(&cell.pos, &pointer) && available_cells.contains(&cell.pos) {
-            let mut cloned_selected_piece = selected_piece.clone();
-            let (maybe_removed_piece, pieces_after_move) =
-                ChessPiece::pieces_after_move(&pieces, &cell.pos, &mut cloned_selected_piece);
-
-            let move_not_allowed =
-                ChessPiece::is_king_under_check(&selected_piece.color, &pieces_after_move, &board);
-            if move_not_allowed {
-                return;
-            }
+        if !board.is_cell_matches(&cell.pos, &pointer) {
+            continue;
+        }
+        let pieces: Vec<(Entity, &ChessPiece)> = q_chess_piece
+            .iter()
+            .map(|(entity, cp)| (entity, &cp.0))
+            .collect();
+        make_selected_piece_move(
+            &mut move_state,
+            &selected_piece.pos,
+            (entity, cell),
+            &pieces,
+            &mut piece_taken_event_writer,
+        );
+    }
+}
 
-            let color = selected_piece.color.opposite();
-            if ChessPiece::is_king_under_check(&color, &pieces_after_move, &board) {
-                if ChessPiece::is_king_under_mate(&color, &pieces_after_move, &board) {
-                    warn!("king mate state");
-                    move_state.mate_state = Option::Some(color);
-                    move_state.check_state = None;
-                } else {
-                    move_state.check_state = Option::Some(color);
-                    move_state.mate_state = None;
-                }
-            } else {
-                move_state.check_state = None;
-                move_state.mate_state = None;
-            }
+// the picked up piece follows the pointer above the other pieces
+fn drag_piece_system(
+    buttons: Res<Input<MouseButton>>,
+    pointer: Res<BoardPointer>,
+    board: Res<Board>,
+    move_state: Res<GameMoveState>,
+    piece_drag: Res<PieceDrag>,
+    mut q_transform: Query<&mut Transform, With<BoardPiece>>,
+) {
+    if !buttons.pressed(MouseButton::Left) || move_state.move_in_action {
+        return;
+    }
+    let Some(entity) = piece_drag
+        .piece
+        .filter(|entity| Some(*entity) == move_state.selected_piece)
+    else {
+        return;
+    };
+    if let Ok(mut transform) = q_transform.get_mut(entity) {
+        transform.translation = Vec3::new(
+            pointer.x - board.board_offset(),
+            pointer.y - board.board_offset(),
+            DRAGGED_PIECE_Z,
+        );
+    }
+}
+
+// releasing over a legal cell makes the move, anywhere else the piece goes back to its cell
+#[allow(clippy::too_many_arguments)]
+fn drop_piece_system(
+    buttons: Res<Input<MouseButton>>,
+    pointer: Res<BoardPointer>,
+    board: Res<Board>,
+    mut move_state: ResMut<GameMoveState>,
+    mut piece_drag: ResMut<PieceDrag>,
+    browse_state: Res<State<BrowseState>>,
+    q_chess_cell: Query<(Entity, &ChessCell)>,
+    mut q_chess_piece: Query<(Entity, &BoardPiece, &mut Transform)>,
+    mut piece_taken_event_writer: EventWriter<ChessPieceRemovedEvent>,
+) {
+    let Some(entity) = piece_drag.piece else {
+        return;
+    };
+    let is_still_carried = Some(entity) == move_state.selected_piece
+        && !move_state.move_in_action
+        && browse_state.0 == BrowseState::Latest;
+    if is_still_carried && !buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    piece_drag.piece = None;
+    let Ok((_, from, _)) = q_chess_piece.get(entity) else {
+        return;
+    };
+    let from = from.pos;
 
-            if let Some(piece_to_remove) = maybe_removed_piece {
-                piece_taken_event_writer.send(ChessPieceRemovedEvent {
-                    chess_piece: ChessPiece {
-                        pos: piece_to_remove.pos,
-                        color: piece_to_remove.color.clone(),
-                        piece_type: piece_to_remove.piece_type.clone(),
-                    },
-                });
+    if is_still_carried {
+        if board.is_cell_matches(&from, &pointer) {
+            if piece_drag.was_selected {
+                move_state.selected_piece = None;
             }
+        } else if let Some(cell) = q_chess_cell
+            .iter()
+            .find(|(_, cell)| board.is_cell_matches(&cell.pos, &pointer))
+        {
+            let pieces: Vec<(Entity, &ChessPiece)> = q_chess_piece
+                .iter()
+                .map(|(entity, cp, _)| (entity, &cp.0))
+                .collect();
+            make_selected_piece_move(
+                &mut move_state,
+                &from,
+                cell,
+                &pieces,
+                &mut piece_taken_event_writer,
+            );
+        }
+    }
 
-            move_state.selected_cell = Some(entity);
-            move_state.move_in_action = true;
+    let Ok((_, _, mut transform)) = q_chess_piece.get_mut(entity) else {
+        return;
+    };
+    // a made move glides on from the pointer to its cell
+    if !(move_state.move_in_action && Some(entity) == move_state.selected_piece) {
+        let (x, y) = board.coordinates(&from);
+        transform.translation.x = x;
+        transform.translation.y = y;
+    }
+    transform.translation.z = PIECES_Z;
+}
+
+// makes the move of the selected piece when the rules allow it and starts its animation
+fn make_selected_piece_move(
+    move_state: &mut GameMoveState,
+    from: &CellPosition,
+    (cell_entity, cell): (Entity, &ChessCell),
+    pieces: &[(Entity, &ChessPiece)],
+    piece_taken_event_writer: &mut EventWriter<ChessPieceRemovedEvent>,
+) {
+    let mut board_pieces: Vec<ChessPiece> = pieces.iter().map(|(_, cp)| (*cp).clone()).collect();
+    let Some(chess_move) = move_state.make_move(&mut board_pieces, from, &cell.pos, None) else {
+        return;
+    };
+    start_move_animation(
+        move_state,
+        chess_move,
+        pieces,
+        cell_entity,
+        piece_taken_event_writer,
+    );
+}
+
+// animates a move of the computer or the remote player already made in move_state,
+// a promoted piece glides to the last rank already replaced
+pub fn start_made_move_animation(
+    move_state: &mut GameMoveState,
+    chess_move: ChessMove,
+    q_chess_cell: &Query<(Entity, &ChessCell)>,
+    q_chess_piece: &mut Query<(Entity, &mut BoardPiece, &mut Handle<Image>)>,
+    assets: &AssetServer,
+    piece_taken_event_writer: &mut EventWriter<ChessPieceRemovedEvent>,
+) {
+    let Some((cell_entity, _)) = q_chess_cell
+        .iter()
+        .find(|(_, cell)| cell.pos == chess_move.to)
+    else {
+        return;
+    };
+    if let Some(piece_type) = &chess_move.promotion {
+        for (_, mut chess_piece, mut image) in q_chess_piece.iter_mut() {
+            if chess_piece.pos == chess_move.piece.pos {
+                chess_piece.piece_type = piece_type.clone();
+                *image = AssetsHelper::load_piece_image(&chess_piece.color, piece_type, assets);
+            }
         }
     }
+
+    let pieces: Vec<(Entity, &ChessPiece)> = q_chess_piece
+        .iter()
+        .map(|(entity, cp, _)| (entity, &cp.0))
+        .collect();
+    start_move_animation(
+        move_state,
+        chess_move,
+        &pieces,
+        cell_entity,
+        piece_taken_event_writer,
+    );
+}
+
+// animates a move already made in move_state, the same way for the player and the computer
+pub fn start_move_animation(
+    move_state: &mut GameMoveState,
+    chess_move: ChessMove,
+    pieces: &[(Entity, &ChessPiece)],
+    cell_entity: Entity,
+    piece_taken_event_writer: &mut EventWriter<ChessPieceRemovedEvent>,
+) {
+    if let Some(piece_to_remove) = chess_move.captured_piece {
+        piece_taken_event_writer.send(ChessPieceRemovedEvent {
+            chess_piece: piece_to_remove,
+        });
+    }
+
+    let piece_entity = |pos: CellPosition| {
+        pieces
+            .iter()
+            .find(|(_, cp)| cp.pos == pos)
+            .map(|(entity, _)| *entity)
+    };
+    move_state.selected_piece = piece_entity(chess_move.piece.pos);
+    move_state.castling_rook = chess_move
+        .castling_rook
+        .and_then(|(rook_from, rook_to)| piece_entity(rook_from).map(|entity| (entity, rook_to)));
+    move_state.selected_cell = Some(cell_entity);
+    move_state.move_in_action = true;
 }
 
 fn move_piece_system(
     time: Res<Time>,
     board: Res<Board>,
-    mut move_state: ResMut<MoveState>,
+    mut move_state: ResMut<GameMoveState>,
     q_chess_cell: Query<&ChessCell>,
-    mut q_chess_piece: Query<(&mut ChessPiece, &mut Transform)>,
+    mut q_chess_piece: Query<(&mut BoardPiece, &mut Transform)>,
 ) {
     if !move_state.move_in_action {
         return;
@@ -451,7 +685,37 @@ fn move_piece_system(
 
     chess_piece.pos = chess_cell.pos;
 
-    let (target_x, target_y) = board.coordinates(&chess_piece.pos);
+    let is_move_finished = move_piece_transform(&board, &time, &chess_piece.pos, &mut transform);
+
+    if let Some((rook_entity, rook_cell)) = move_state.castling_rook {
+        let (mut rook, mut rook_transform) = q_chess_piece.get_mut(rook_entity).unwrap();
+        rook.pos = rook_cell;
+        move_piece_transform(&board, &time, &rook_cell, &mut rook_transform);
+        if is_move_finished {
+            let (target_x, target_y) = board.coordinates(&rook_cell);
+            rook_transform.translation.x = target_x;
+            rook_transform.translation.y = target_y;
+        }
+    }
+
+    if is_move_finished {
+        if move_state.promotion_cell.is_some() {
+            // the turn passes once the promotion piece is picked
+            move_state.end_move_animation();
+        } else {
+            move_state.next_move();
+        }
+    }
+}
+
+// moves the sprite towards the cell, returns true and snaps to the cell once it is reached
+fn move_piece_transform(
+    board: &Board,
+    time: &Time,
+    cell_position: &CellPosition,
+    transform: &mut Transform,
+) -> bool {
+    let (target_x, target_y) = board.coordinates(cell_position);
 
     let target_vec = Vec3::new(target_x, target_y, transform.translation.z);
 
@@ -460,25 +724,27 @@ fn move_piece_system(
         .translation
         .lerp(target_vec, velocity * time.delta_seconds());
 
-    if transform.translation.abs_diff_eq(target_vec, 1.0) {
-        move_state.move_in_action = false;
-        move_state.selected_cell = None;
-        move_state.selected_piece = None;
-
+    let is_reached = transform.translation.abs_diff_eq(target_vec, 1.0);
+    if is_reached {
         transform.translation.x = target_x;
         transform.translation.y = target_y;
-        move_state.next_move();
     }
+    is_reached
 }
 
 fn remove_taken_piece_system(
     mut commands: Commands,
-    q_chess_piece: Query<(Entity, &ChessPiece)>,
+    q_chess_piece: Query<(Entity, &BoardPiece)>,
     mut piece_taken_event_reader: EventReader<ChessPieceRemovedEvent>,
 ) {
+    // the event holds the captured piece's own cell, so en passant captures
+    // remove the pawn beside the destination rather than on it
     piece_taken_event_reader.iter().for_each(|event| {
         q_chess_piece.iter().for_each(|(entity, cp)| {
-            if cp.pos == event.chess_piece.pos && cp.color == event.chess_piece.color {
+            if cp.pos == event.chess_piece.pos
+                && cp.color == event.chess_piece.color
+                && cp.piece_type == event.chess_piece.piece_type
+            {
                 commands.entity(entity).despawn();
             }
         })
@@ -0,0 +1,79 @@
+use std::env;
+
+use bevy::prelude::*;
+
+use crate::models::{
+    app_state::AppState,
+    clock::{Clock, ClockPreset, TimeControl},
+    common_resources::{GameMode, GameState, LoadedGame},
+    game_move_state::GameMoveState,
+};
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Clock::default())
+            .add_startup_system(clock_from_args)
+            .add_system(set_up_clock.in_schedule(OnEnter(AppState::Game)))
+            .add_system(clock_system.in_set(OnUpdate(AppState::Game)));
+    }
+}
+
+// `--clock <minutes>+<increment>` or `--clock <minutes>d<delay>` sets a custom time control
+fn clock_from_args(mut clock: ResMut<Clock>) {
+    let args: Vec<String> = env::args().collect();
+    let Some(value) = args
+        .iter()
+        .position(|arg| arg == "--clock")
+        .and_then(|index| args.get(index + 1))
+    else {
+        return;
+    };
+    match TimeControl::parse(value) {
+        Some(time_control) => clock.preset = ClockPreset::CUSTOM(time_control),
+        None => warn!("invalid time control {}, expected e.g. 5+3 or 5d3", value),
+    }
+}
+
+// network games are untimed, the clocks of both ends would not agree
+fn set_up_clock(
+    game_state: Res<GameState>,
+    game_mode: Res<GameMode>,
+    mut loaded_game: ResMut<LoadedGame>,
+    mut clock: ResMut<Clock>,
+) {
+    match *game_state {
+        GameState::NEW => clock.reset(),
+        GameState::CONTINUE => {}
+        GameState::LOAD => match loaded_game.clock.take() {
+            Some(saved_clock) => *clock = saved_clock,
+            None => clock.reset(),
+        },
+    }
+    if game_mode.net_role.is_some() {
+        clock.stop();
+    }
+}
+
+// runs the clock of the side to move, the systems only run on the game screen
+// so the clocks stand still in the main menu; flag-fall ends the game
+fn clock_system(time: Res<Time>, mut clock: ResMut<Clock>, mut move_state: ResMut<GameMoveState>) {
+    if !clock.is_timed() || move_state.is_game_over() {
+        return;
+    }
+    clock.follow_ply(
+        move_state.ply_count,
+        move_state.redo_history.len() as u32,
+        &move_state.current_collor.opposite(),
+    );
+
+    // nobody is on move while stepping through taken back moves
+    if !move_state.redo_history.is_empty() {
+        return;
+    }
+    let color = move_state.current_collor.clone();
+    if clock.tick(&color, time.delta()) {
+        move_state.flag_state = Some(color);
+    }
+}
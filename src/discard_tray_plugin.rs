@@ -3,11 +3,13 @@ use std::collections::HashMap;
 
 use crate::assets_helper::AssetsHelper;
 use crate::models::app_state::AppState;
-use crate::models::chess_piece::PieceType;
-use crate::models::common_chess::ChessColor;
-use crate::models::common_resources::GameState;
-use crate::models::removed_chess_piece::{ChessPieceRemovedEvent, RemovedChessPiece};
+use crate::models::common_resources::{GameState, LoadedGame};
+use crate::models::removed_chess_piece::{
+    ChessPieceRemovedEvent, ChessPieceRestoredEvent, RemovedChessPiece,
+};
 use crate::{App, Board, Plugin};
+use chess_engine::chess_piece::PieceType;
+use chess_engine::common_chess::ChessColor;
 
 #[derive(Default, Resource)]
 struct DiscardTrayHolder {
@@ -26,20 +28,37 @@ impl Plugin for DiscardTrayPlugin {
             .insert_resource(DiscardPiecesStore::default())
             .add_system(set_up_resources.in_schedule(OnEnter(AppState::Game)))
             .add_system(despawn_discard_tray_pieces.in_schedule(OnExit(AppState::Game)))
-            .add_system(add_taken_piece_to_discard_tray.in_set(OnUpdate(AppState::Game)));
+            .add_systems(
+                (
+                    add_taken_piece_to_discard_tray,
+                    remove_restored_piece_from_discard_tray,
+                )
+                    .in_set(OnUpdate(AppState::Game)),
+            );
     }
 }
 
 fn set_up_resources(
     mut commands: Commands,
     game_state: Res<GameState>,
+    loaded_game: Res<LoadedGame>,
     mut discard_tray_holder: ResMut<DiscardTrayHolder>,
     mut pieces_store: ResMut<DiscardPiecesStore>,
     board: Res<Board>,
     assets: Res<AssetServer>,
 ) {
-    if let GameState::NEW = *game_state {
-        pieces_store.state = vec![];
+    match *game_state {
+        GameState::NEW => pieces_store.state = vec![],
+        GameState::CONTINUE => {}
+        GameState::LOAD => {
+            pieces_store.state = loaded_game
+                .move_state
+                .iter()
+                .flat_map(|move_state| move_state.move_history.iter())
+                .filter_map(|chess_move| chess_move.captured_piece.as_ref())
+                .map(|piece| (piece.color.clone(), piece.piece_type.clone()))
+                .collect();
+        }
     }
     for (color, piece_type) in pieces_store.state.iter() {
         add_to_dicard(
@@ -92,6 +111,27 @@ fn add_taken_piece_to_discard_tray(
     });
 }
 
+// undo takes captures back in reverse order, so the restored piece is the last one of its color
+fn remove_restored_piece_from_discard_tray(
+    mut commands: Commands,
+    mut piece_restored_event_reader: EventReader<ChessPieceRestoredEvent>,
+    mut discard_tray: ResMut<DiscardTrayHolder>,
+    q_removed_piece: Query<(Entity, &RemovedChessPiece)>,
+) {
+    piece_restored_event_reader.iter().for_each(|event| {
+        let color = &event.chess_piece.color;
+        let Some(element_num) = discard_tray.value.get(color).map(|v| *v - 1) else {
+            return;
+        };
+        for (entity, removed_piece) in q_removed_piece.iter() {
+            if removed_piece.color == *color && removed_piece.num == element_num {
+                commands.entity(entity).despawn();
+            }
+        }
+        discard_tray.value.insert(color.clone(), element_num);
+    });
+}
+
 fn add_to_dicard(
     color: &ChessColor,
     piece_type: &PieceType,
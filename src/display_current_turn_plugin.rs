@@ -1,14 +1,15 @@
 use bevy::prelude::*;
 use bevy::text::Text2dBounds;
+use chess_engine::{chess_piece::PieceType, common_chess::ChessColor};
 
 use crate::{
+    ai_plugin::ComputerTask,
     assets_helper::AssetsHelper,
     models::{
         app_state::AppState,
-        chess_move_state::MoveState,
-        chess_piece::PieceType,
-        common_chess::ChessColor,
+        clock::Clock,
         common_resources::{Board, FontHolder},
+        game_move_state::GameMoveState,
     },
     titles::Titles,
 };
@@ -38,6 +39,20 @@ struct CurentTurnText;
 struct CheckStateText;
 #[derive(Component)]
 struct MateStateText;
+#[derive(Component)]
+struct StalemateStateText;
+#[derive(Component)]
+struct DrawStateText;
+#[derive(Component)]
+struct ThinkingText;
+#[derive(Component)]
+struct FlagStateText;
+#[derive(Component)]
+struct ResignStateText;
+
+// remaining time of the side
+#[derive(Component)]
+struct ClockText(ChessColor);
 
 #[derive(Component)]
 struct Despawnable;
@@ -57,6 +72,12 @@ impl Plugin for DisplayCurrentTurnPlugin {
                 display_current_turn_system,
                 display_check_state_system,
                 display_mate_state_system,
+                display_stalemate_state_system,
+                display_draw_state_system,
+                display_thinking_system,
+                display_flag_state_system,
+                display_resign_state_system,
+                display_clock_system,
             )
                 .in_set(OnUpdate(AppState::Game)),
         );
@@ -163,6 +184,139 @@ fn set_up_display_turn_components(
         })
         .insert(MateStateText)
         .insert(Despawnable);
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                titles.stalemate.clone(),
+                TextStyle {
+                    font: font_holder.font.clone(),
+                    font_size,
+                    color: Color::RED,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform {
+                translation: Vec3::new(text_x, text_y - font_size, text_z),
+                scale: Vec3::splat(1.0),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StalemateStateText)
+        .insert(Despawnable);
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font_holder.font.clone(),
+                    font_size,
+                    color: Color::RED,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform {
+                translation: Vec3::new(text_x, text_y - font_size, text_z),
+                scale: Vec3::splat(1.0),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(DrawStateText)
+        .insert(Despawnable);
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                titles.thinking.clone(),
+                TextStyle {
+                    font: font_holder.font.clone(),
+                    font_size,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform {
+                translation: Vec3::new(text_x, text_y - 2. * font_size, text_z),
+                scale: Vec3::splat(1.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(ThinkingText)
+        .insert(Despawnable);
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                titles.flag_fall.clone(),
+                TextStyle {
+                    font: font_holder.font.clone(),
+                    font_size,
+                    color: Color::RED,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform {
+                translation: Vec3::new(text_x, text_y - 2. * font_size, text_z),
+                scale: Vec3::splat(1.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(FlagStateText)
+        .insert(Despawnable);
+
+    commands
+        .spawn(Text2dBundle {
+            text: Text::from_section(
+                titles.resigned.clone(),
+                TextStyle {
+                    font: font_holder.font.clone(),
+                    font_size,
+                    color: Color::RED,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            transform: Transform {
+                translation: Vec3::new(text_x, text_y - 2. * font_size, text_z),
+                scale: Vec3::splat(1.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .insert(ResignStateText)
+        .insert(Despawnable);
+
+    // black's clock above white's as on the board
+    for (color, row) in [(ChessColor::WHITE, 1.), (ChessColor::BLACK, 2.)] {
+        commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: font_holder.font.clone(),
+                        font_size,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                transform: Transform {
+                    translation: Vec3::new(text_x, text_y + row * font_size, text_z),
+                    scale: Vec3::splat(1.0),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            })
+            .insert(ClockText(color))
+            .insert(Despawnable);
+    }
 }
 
 fn despawn_display_turn_components(
@@ -183,7 +337,7 @@ fn display_current_turn_system(
         (&mut Handle<Image>, &mut Transform),
         (With<CurentTurnImage>, Without<CurentTurnText>),
     >,
-    move_state: Res<MoveState>,
+    move_state: Res<GameMoveState>,
     turn_image_holder: Res<TurnImageHolder>,
     board: Res<Board>,
 ) {
@@ -202,7 +356,7 @@ fn display_current_turn_system(
 
 fn display_check_state_system(
     mut q_check_status: Query<&mut Visibility, With<CheckStateText>>,
-    move_state: Res<MoveState>,
+    move_state: Res<GameMoveState>,
 ) {
     let mut check_state_visibility = q_check_status.single_mut();
     let visibility = match move_state.check_state {
@@ -214,12 +368,100 @@ fn display_check_state_system(
 
 fn display_mate_state_system(
     mut q_mate_status: Query<&mut Visibility, With<MateStateText>>,
-    move_state: Res<MoveState>,
+    move_state: Res<GameMoveState>,
 ) {
     let mut mate_state_visibility = q_mate_status.single_mut();
-    let visibility = match move_state.check_state {
+    let visibility = match move_state.mate_state {
         None => Visibility::Hidden,
         Some(_) => Visibility::Visible,
     };
     *mate_state_visibility = visibility;
 }
+
+fn display_stalemate_state_system(
+    mut q_stalemate_status: Query<&mut Visibility, With<StalemateStateText>>,
+    move_state: Res<GameMoveState>,
+) {
+    let mut stalemate_state_visibility = q_stalemate_status.single_mut();
+    let visibility = match move_state.is_stalemate_state {
+        None => Visibility::Hidden,
+        Some(_) => Visibility::Visible,
+    };
+    *stalemate_state_visibility = visibility;
+}
+
+fn display_draw_state_system(
+    mut q_draw_status: Query<(&mut Text, &mut Visibility), With<DrawStateText>>,
+    move_state: Res<GameMoveState>,
+    titles: Res<Titles>,
+) {
+    let (mut text, mut draw_state_visibility) = q_draw_status.single_mut();
+    *draw_state_visibility = match &move_state.draw_state {
+        None => Visibility::Hidden,
+        Some(reason) => {
+            text.sections[0].value = titles.draw_reason(reason);
+            Visibility::Visible
+        }
+    };
+}
+
+fn display_thinking_system(
+    mut q_thinking: Query<&mut Visibility, With<ThinkingText>>,
+    computer_task: Res<ComputerTask>,
+) {
+    let mut thinking_visibility = q_thinking.single_mut();
+    *thinking_visibility = if computer_task.is_thinking() {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    };
+}
+
+fn display_flag_state_system(
+    mut q_flag_status: Query<&mut Visibility, With<FlagStateText>>,
+    move_state: Res<GameMoveState>,
+) {
+    let mut flag_state_visibility = q_flag_status.single_mut();
+    *flag_state_visibility = match move_state.flag_state {
+        None => Visibility::Hidden,
+        Some(_) => Visibility::Visible,
+    };
+}
+
+fn display_resign_state_system(
+    mut q_resign_status: Query<&mut Visibility, With<ResignStateText>>,
+    move_state: Res<GameMoveState>,
+) {
+    let mut resign_state_visibility = q_resign_status.single_mut();
+    *resign_state_visibility = match move_state.resign_state {
+        None => Visibility::Hidden,
+        Some(_) => Visibility::Visible,
+    };
+}
+
+// the running clock is bright, the waiting one is dimmed
+fn display_clock_system(
+    mut q_clock: Query<(&ClockText, &mut Text, &mut Visibility)>,
+    clock: Res<Clock>,
+    move_state: Res<GameMoveState>,
+    titles: Res<Titles>,
+) {
+    for (clock_text, mut text, mut visibility) in q_clock.iter_mut() {
+        if !clock.is_timed() {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        *visibility = Visibility::Visible;
+        let color = &clock_text.0;
+        text.sections[0].value = format!(
+            "{} {}",
+            titles.clock(color),
+            Clock::format(clock.remaining(color))
+        );
+        text.sections[0].style.color = if *color == move_state.current_collor {
+            Color::WHITE
+        } else {
+            Color::GRAY
+        };
+    }
+}
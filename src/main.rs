@@ -1,35 +1,70 @@
+use ai_plugin::AiPlugin;
 use bevy::prelude::*;
-
 use chess_board_plugin::ChessBoardPlugin;
+use chess_engine::{net_peer::HeadlessPeer, perft::Perft};
+use clock_plugin::ClockPlugin;
 use cursor_cords_plugin::CursorCordsPlugin;
 use custom_cursor_plugin::CustomCursorPlugin;
 use display_current_turn_plugin::DisplayCurrentTurnPlugin;
 use models::{
     app_state::AppState,
-    common_resources::{Board, BoardPointer, FontHolder, GameState, MainCamera},
+    common_resources::{
+        Board, BoardPointer, FontHolder, GameMode, GameState, LoadedGame, MainCamera,
+    },
 };
+use move_list_plugin::MoveListPlugin;
+use net_plugin::NetPlugin;
+use pgn_plugin::PgnPlugin;
+use promotion_plugin::PromotionPlugin;
+use save_game_plugin::SaveGamePlugin;
 use titles::{TitleLocale, Titles};
 use ui_menu_plugin::UiMenuPlugin;
+use undo_plugin::UndoPlugin;
 
 use crate::discard_tray_plugin::DiscardTrayPlugin;
 
+mod ai_plugin;
 mod assets_helper;
 mod chess_board_plugin;
+mod clock_plugin;
 mod cursor_cords_plugin;
 mod custom_cursor_plugin;
 mod discard_tray_plugin;
 mod display_current_turn_plugin;
 mod models;
-mod piece_parser;
+mod move_list_plugin;
+mod net_plugin;
+mod pgn_plugin;
+mod promotion_plugin;
+mod save_game_plugin;
 mod titles;
 mod ui_menu_plugin;
+mod undo_plugin;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("perft") {
+        if let Err(err) = Perft::run_command(&args[2..]) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("peer") {
+        if let Err(err) = HeadlessPeer::run_command(&args[2..]) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let titles = Titles::new(TitleLocale::RU);
     let window_title = titles.title.clone();
     App::new()
         .insert_resource(ClearColor(Color::rgb(0.04, 0.30, 0.40)))
         .insert_resource(GameState::NEW)
+        .insert_resource(LoadedGame::default())
+        .insert_resource(GameMode::default())
         .insert_resource(titles)
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -50,6 +85,14 @@ fn main() {
         .add_plugin(DiscardTrayPlugin)
         .add_plugin(DisplayCurrentTurnPlugin)
         .add_plugin(UiMenuPlugin)
+        .add_plugin(PromotionPlugin)
+        .add_plugin(PgnPlugin)
+        .add_plugin(UndoPlugin)
+        .add_plugin(MoveListPlugin)
+        .add_plugin(AiPlugin)
+        .add_plugin(ClockPlugin)
+        .add_plugin(SaveGamePlugin)
+        .add_plugin(NetPlugin)
         .add_system(change_game_state)
         .run();
 }
@@ -4,5 +4,24 @@ use bevy::prelude::States;
 pub enum AppState {
     #[default]
     MainMenu,
+    // save slots opened from the main menu
+    SaveMenu,
     Game,
 }
+
+// sub-state of AppState::Game, moves are blocked while a promotion piece is picked
+#[derive(Default, Hash, Debug, PartialEq, Eq, Clone, States)]
+pub enum PromotionState {
+    #[default]
+    Inactive,
+    Picking,
+}
+
+// sub-state of AppState::Game, an earlier position picked in the move list is shown
+// read-only until the latest move is back on the board
+#[derive(Default, Hash, Debug, PartialEq, Eq, Clone, States)]
+pub enum BrowseState {
+    #[default]
+    Latest,
+    Browsing,
+}
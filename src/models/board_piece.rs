@@ -0,0 +1,6 @@
+use bevy::prelude::*;
+use chess_engine::chess_piece::ChessPiece;
+
+// piece sprite on the board, the rules see the wrapped engine piece
+#[derive(Component, Clone, Debug, Deref, DerefMut)]
+pub struct BoardPiece(pub ChessPiece);
@@ -1,12 +1,19 @@
 use bevy::prelude::*;
+use chess_engine::common_chess::{CellPosition, ChessColor};
 
-use super::{common_chess::ChessColor, common_resources::CellPosition};
-
+// selection and hover states are drawn over the last move and check ones
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum ChessCellState {
     NONE,
     HIGHLIGHTED,
     SELECTED,
     ATTACKED,
+    // cells the last move was made from and to
+    LAST_MOVE_FROM,
+    LAST_MOVE_TO,
+    // cell of the king of the side to move while it is in check
+    IN_CHECK,
 }
 
 #[derive(Component)]
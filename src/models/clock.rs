@@ -0,0 +1,363 @@
+use std::{fmt, time::Duration};
+
+use bevy::prelude::Resource;
+use chess_engine::common_chess::ChessColor;
+
+// how the bonus of a time control is given for a move
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimeMode {
+    // the bonus is added after every move
+    FISCHER,
+    // the time spent on the move is given back up to the bonus
+    BRONSTEIN,
+}
+
+// starting time of each side and the bonus per move
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub bonus: Duration,
+    pub mode: TimeMode,
+}
+
+impl TimeControl {
+    pub fn new(base_secs: u64, bonus_secs: u64, mode: TimeMode) -> TimeControl {
+        TimeControl {
+            base: Duration::from_secs(base_secs),
+            bonus: Duration::from_secs(bonus_secs),
+            mode,
+        }
+    }
+
+    // "<minutes>+<seconds>" with an increment or "<minutes>d<seconds>" with a delay
+    pub fn parse(value: &str) -> Option<TimeControl> {
+        let (separator, mode) = if value.contains('d') {
+            ('d', TimeMode::BRONSTEIN)
+        } else {
+            ('+', TimeMode::FISCHER)
+        };
+        let (minutes, seconds) = value.split_once(separator).unwrap_or((value, "0"));
+        let minutes: u64 = minutes.trim().parse().ok()?;
+        let seconds: u64 = seconds.trim().parse().ok()?;
+        if minutes == 0 {
+            return None;
+        }
+        Some(TimeControl::new(minutes * 60, seconds, mode))
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = match self.mode {
+            TimeMode::FISCHER => '+',
+            TimeMode::BRONSTEIN => 'd',
+        };
+        write!(
+            f,
+            "{}{}{}",
+            self.base.as_secs() / 60,
+            separator,
+            self.bonus.as_secs()
+        )
+    }
+}
+
+// time controls picked in the main menu, custom comes from the `--clock` argument
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Default, Clone, PartialEq, Debug)]
+pub enum ClockPreset {
+    #[default]
+    UNLIMITED,
+    BULLET,
+    BLITZ,
+    RAPID,
+    CUSTOM(TimeControl),
+}
+
+impl ClockPreset {
+    pub fn time_control(&self) -> Option<TimeControl> {
+        match self {
+            ClockPreset::UNLIMITED => None,
+            ClockPreset::BULLET => Some(TimeControl::new(60, 1, TimeMode::FISCHER)),
+            ClockPreset::BLITZ => Some(TimeControl::new(3 * 60, 2, TimeMode::FISCHER)),
+            ClockPreset::RAPID => Some(TimeControl::new(10 * 60, 5, TimeMode::FISCHER)),
+            ClockPreset::CUSTOM(time_control) => Some(time_control.clone()),
+        }
+    }
+
+    // name of a built-in preset or the time control of a custom one
+    pub fn parse(value: &str) -> Option<ClockPreset> {
+        match value {
+            "unlimited" => Some(ClockPreset::UNLIMITED),
+            "bullet" => Some(ClockPreset::BULLET),
+            "blitz" => Some(ClockPreset::BLITZ),
+            "rapid" => Some(ClockPreset::RAPID),
+            _ => TimeControl::parse(value).map(ClockPreset::CUSTOM),
+        }
+    }
+
+    // next preset of the menu button, a custom one is left for the built-in presets
+    pub fn next(&self) -> ClockPreset {
+        match self {
+            ClockPreset::UNLIMITED => ClockPreset::BULLET,
+            ClockPreset::BULLET => ClockPreset::BLITZ,
+            ClockPreset::BLITZ => ClockPreset::RAPID,
+            ClockPreset::RAPID | ClockPreset::CUSTOM(_) => ClockPreset::UNLIMITED,
+        }
+    }
+}
+
+impl fmt::Display for ClockPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClockPreset::UNLIMITED => write!(f, "unlimited"),
+            ClockPreset::BULLET => write!(f, "bullet"),
+            ClockPreset::BLITZ => write!(f, "blitz"),
+            ClockPreset::RAPID => write!(f, "rapid"),
+            ClockPreset::CUSTOM(time_control) => write!(f, "{}", time_control),
+        }
+    }
+}
+
+// remaining time of both sides, it only runs while the game screen is shown
+#[derive(Default, Clone, Resource)]
+pub struct Clock {
+    pub preset: ClockPreset,
+    time_control: Option<TimeControl>,
+    white_time: Duration,
+    black_time: Duration,
+    // time the side to move has spent on the current move
+    move_time: Duration,
+    // ply the running move belongs to, None until the first tick of a game
+    ply_count: Option<u32>,
+    // latest ply of the game so far, moves up to it are only taken back or made again
+    latest_ply: Option<u32>,
+}
+
+impl Clock {
+    // sets both sides to the starting time of the picked preset
+    pub fn reset(&mut self) {
+        self.time_control = self.preset.time_control();
+        let base = self
+            .time_control
+            .as_ref()
+            .map_or(Duration::ZERO, |time_control| time_control.base);
+        self.white_time = base;
+        self.black_time = base;
+        self.move_time = Duration::ZERO;
+        self.ply_count = None;
+        self.latest_ply = None;
+    }
+
+    // clock of a saved game going on with the saved remaining times
+    pub fn restore(preset: ClockPreset, white_time: Duration, black_time: Duration) -> Clock {
+        let mut clock = Clock {
+            preset,
+            ..Default::default()
+        };
+        clock.reset();
+        clock.white_time = white_time;
+        clock.black_time = black_time;
+        clock
+    }
+
+    // no time control for the game, the picked preset is kept for the next one
+    pub fn stop(&mut self) {
+        self.time_control = None;
+    }
+
+    pub fn is_timed(&self) -> bool {
+        self.time_control.is_some()
+    }
+
+    pub fn remaining(&self, color: &ChessColor) -> Duration {
+        match color {
+            ChessColor::WHITE => self.white_time,
+            ChessColor::BLACK => self.black_time,
+        }
+    }
+
+    // runs the clock of the side to move, true once its time is over
+    pub fn tick(&mut self, color: &ChessColor, delta: Duration) -> bool {
+        if !self.is_timed() {
+            return false;
+        }
+        self.move_time += delta;
+        let remaining = self.remaining_mut(color);
+        *remaining = remaining.saturating_sub(delta);
+        remaining.is_zero()
+    }
+
+    // gives the bonus of the time control for the move the side has just made
+    pub fn complete_move(&mut self, color: &ChessColor) {
+        let Some(time_control) = self.time_control.clone() else {
+            return;
+        };
+        let bonus = match time_control.mode {
+            TimeMode::FISCHER => time_control.bonus,
+            TimeMode::BRONSTEIN => self.move_time.min(time_control.bonus),
+        };
+        *self.remaining_mut(color) += bonus;
+        self.move_time = Duration::ZERO;
+    }
+
+    // follows the ply of the game, the bonus is given once for each newly made move;
+    // taken back moves and the ones made again with redo only end the running move
+    pub fn follow_ply(&mut self, ply_count: u32, redo_count: u32, mover: &ChessColor) {
+        let latest_ply = *self.latest_ply.get_or_insert(ply_count + redo_count);
+        match self.ply_count {
+            Some(last_ply) if last_ply + 1 == ply_count && ply_count > latest_ply => {
+                self.complete_move(mover)
+            }
+            Some(last_ply) if last_ply != ply_count => self.skip_move(),
+            _ => {}
+        }
+        self.latest_ply = Some(latest_ply.max(ply_count));
+        self.ply_count = Some(ply_count);
+    }
+
+    // moves taken back or made again do not change the time
+    pub fn skip_move(&mut self) {
+        self.move_time = Duration::ZERO;
+    }
+
+    // "m:ss", tenths are shown for the last ten seconds
+    pub fn format(duration: Duration) -> String {
+        let secs = duration.as_secs();
+        if secs < 10 {
+            format!("0:{:02}.{}", secs, duration.subsec_millis() / 100)
+        } else {
+            format!("{}:{:02}", secs / 60, secs % 60)
+        }
+    }
+
+    fn remaining_mut(&mut self, color: &ChessColor) -> &mut Duration {
+        match color {
+            ChessColor::WHITE => &mut self.white_time,
+            ChessColor::BLACK => &mut self.black_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use super::*;
+
+    fn new_clock(preset: ClockPreset) -> Clock {
+        let mut clock = Clock {
+            preset,
+            ..Default::default()
+        };
+        clock.reset();
+        clock
+    }
+
+    #[test]
+    fn test_clock_fischer_increment() {
+        let mut clock = new_clock(ClockPreset::BLITZ);
+        assert!(!clock.tick(&ChessColor::WHITE, Duration::from_secs(10)));
+        clock.complete_move(&ChessColor::WHITE);
+        assert_eq!(
+            Duration::from_secs(3 * 60 - 10 + 2),
+            clock.remaining(&ChessColor::WHITE)
+        );
+        assert_eq!(
+            Duration::from_secs(3 * 60),
+            clock.remaining(&ChessColor::BLACK)
+        );
+    }
+
+    #[test]
+    fn test_clock_bonus_once_per_move() {
+        let mut clock = new_clock(ClockPreset::BLITZ);
+        let white = ChessColor::WHITE;
+        clock.follow_ply(0, 0, &ChessColor::BLACK);
+        clock.tick(&white, Duration::from_secs(10));
+        clock.follow_ply(1, 0, &white);
+        let after_move = Duration::from_secs(3 * 60 - 10 + 2);
+        assert_eq!(after_move, clock.remaining(&white));
+
+        // undo and redo of the same move don't pay the bonus again
+        for _ in 0..3 {
+            clock.follow_ply(0, 1, &ChessColor::BLACK);
+            clock.follow_ply(1, 0, &white);
+        }
+        assert_eq!(after_move, clock.remaining(&white));
+
+        // neither does another move made in place of the taken back one
+        clock.follow_ply(0, 1, &ChessColor::BLACK);
+        clock.follow_ply(1, 0, &white);
+        assert_eq!(after_move, clock.remaining(&white));
+
+        clock.follow_ply(2, 0, &ChessColor::BLACK);
+        assert_eq!(
+            Duration::from_secs(3 * 60 + 2),
+            clock.remaining(&ChessColor::BLACK)
+        );
+    }
+
+    #[test]
+    fn test_clock_bronstein_delay() {
+        let time_control = TimeControl::new(60, 5, TimeMode::BRONSTEIN);
+        let mut clock = new_clock(ClockPreset::CUSTOM(time_control));
+        clock.tick(&ChessColor::BLACK, Duration::from_secs(3));
+        clock.complete_move(&ChessColor::BLACK);
+        assert_eq!(Duration::from_secs(60), clock.remaining(&ChessColor::BLACK));
+
+        clock.tick(&ChessColor::BLACK, Duration::from_secs(8));
+        clock.complete_move(&ChessColor::BLACK);
+        assert_eq!(Duration::from_secs(57), clock.remaining(&ChessColor::BLACK));
+    }
+
+    #[test]
+    fn test_clock_flag_fall() {
+        let mut clock = new_clock(ClockPreset::BULLET);
+        assert!(!clock.tick(&ChessColor::WHITE, Duration::from_secs(59)));
+        assert!(clock.tick(&ChessColor::WHITE, Duration::from_secs(2)));
+        assert_eq!(Duration::ZERO, clock.remaining(&ChessColor::WHITE));
+
+        let mut unlimited = new_clock(ClockPreset::UNLIMITED);
+        assert!(!unlimited.tick(&ChessColor::WHITE, Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_time_control_parse() {
+        let time_control = TimeControl::parse("5+3").unwrap();
+        assert_eq!(TimeControl::new(300, 3, TimeMode::FISCHER), time_control);
+        assert_eq!("5+3", time_control.to_string());
+        assert_eq!(
+            Some(TimeControl::new(900, 10, TimeMode::BRONSTEIN)),
+            TimeControl::parse("15d10")
+        );
+        assert_eq!(
+            Some(TimeControl::new(600, 0, TimeMode::FISCHER)),
+            TimeControl::parse("10")
+        );
+        assert_eq!(None, TimeControl::parse("0+5"));
+        assert_eq!(None, TimeControl::parse("five"));
+    }
+
+    #[test]
+    fn test_clock_preset_parse() {
+        for preset in [
+            ClockPreset::UNLIMITED,
+            ClockPreset::BULLET,
+            ClockPreset::BLITZ,
+            ClockPreset::RAPID,
+            ClockPreset::CUSTOM(TimeControl::new(120, 3, TimeMode::BRONSTEIN)),
+        ] {
+            assert_eq!(
+                Some(preset.clone()),
+                ClockPreset::parse(&preset.to_string())
+            );
+        }
+        assert_eq!(None, ClockPreset::parse("classical"));
+    }
+
+    #[test]
+    fn test_clock_format() {
+        assert_eq!("3:05", Clock::format(Duration::from_secs(185)));
+        assert_eq!("0:09.4", Clock::format(Duration::from_millis(9_450)));
+    }
+}
@@ -1,14 +1,81 @@
-use std::ops::Range;
+use std::path::PathBuf;
 
 use bevy::{
     prelude::{Component, Handle, Resource},
     text::Font,
 };
+use chess_engine::{
+    chess_move_state::MoveState,
+    chess_piece::ChessPiece,
+    common_chess::{CellPosition, ChessColor, LAST_ELEMENT},
+};
+
+use super::clock::Clock;
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Resource)]
 pub enum GameState {
     NEW,
     CONTINUE,
+    // start from LoadedGame
+    LOAD,
+}
+
+// game read from a file, used instead of the stored one on GameState::LOAD
+#[derive(Default, Resource)]
+pub struct LoadedGame {
+    pub fen: Option<String>,
+    pub move_state: Option<MoveState>,
+    // clocks of a saved game, a PGN game starts them anew
+    pub clock: Option<Clock>,
+}
+
+// who plays the game, the computer side is None for two players at one board
+#[derive(Default, Resource)]
+pub struct GameMode {
+    pub computer_color: Option<ChessColor>,
+    // side picked in the menu for a game against the computer
+    pub player_color: ChessColor,
+    pub opponent: Opponent,
+    // side of the player at the other end of a network game, known once it said hello
+    pub remote_color: Option<ChessColor>,
+    pub net_role: Option<NetRole>,
+}
+
+impl GameMode {
+    // whether the side is moved by the player at this board
+    pub fn is_local_side(&self, color: &ChessColor) -> bool {
+        // nobody moves before the network peer has joined
+        if self.net_role.is_some() && self.remote_color.is_none() {
+            return false;
+        }
+        self.computer_color.as_ref() != Some(color) && self.remote_color.as_ref() != Some(color)
+    }
+}
+
+// how a network game is connected, the host waits for the other player to join
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, PartialEq, Debug)]
+pub enum NetRole {
+    HOST,
+    JOIN,
+}
+
+// what plays the computer side
+#[derive(Default, Clone, PartialEq, Debug)]
+pub enum Opponent {
+    #[default]
+    BuiltIn,
+    // local UCI engine executable started as a child process
+    UciEngine(PathBuf),
+}
+
+// earlier position picked in the move list, shown on the board while the game goes on;
+// present only while it is browsed
+#[derive(Resource)]
+pub struct BrowsedPosition {
+    pub pieces: Vec<ChessPiece>,
+    pub move_state: MoveState,
 }
 
 #[derive(Resource)]
@@ -30,22 +97,13 @@ pub struct BoardPointer {
     pub y: f32,
 }
 
-// position of a cell on board
-#[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
-pub struct CellPosition {
-    pub i: i8,
-    pub j: i8,
-}
-
-// board control resource
-#[derive(Resource)]
+// board control resource, screen geometry of the 8x8 board of the rules
+#[derive(Clone, Resource)]
 pub struct Board {
     pub start_x_point: f32,
     pub start_y_point: f32,
     pub image_size: f32,
     pub image_scale: f32,
-    pub first_element: i8,
-    pub last_element: i8,
 }
 
 impl Board {
@@ -55,15 +113,9 @@ impl Board {
             start_y_point: y,
             image_size: i_size,
             image_scale: i_scale,
-            first_element: 0,
-            last_element: 7,
         }
     }
 
-    pub fn cell_range(&self) -> Range<i8> {
-        return self.first_element..self.last_element + 1;
-    }
-
     pub fn image_size_scaled(&self) -> f32 {
         self.image_size * self.image_scale
     }
@@ -85,13 +137,6 @@ impl Board {
         self.start_y_point + (pos as f32) * self.image_size_scaled()
     }
 
-    pub fn is_cell_out_of_range(&self, cell: &CellPosition) -> bool {
-        return self.is_out_of_range(cell.i) || self.is_out_of_range(cell.j);
-    }
-    fn is_out_of_range(&self, pos: i8) -> bool {
-        pos < self.first_element || pos > self.last_element
-    }
-
     pub fn is_cell_matches(&self, pos: &CellPosition, pointer: &BoardPointer) -> bool {
         let ref this = self;
         let size = this.image_size_scaled();
@@ -101,11 +146,11 @@ impl Board {
     }
 
     pub fn end_x_point(&self) -> f32 {
-        self.start_x_point + (self.last_element as f32 + 1.) * self.image_size_scaled()
+        self.start_x_point + (LAST_ELEMENT as f32 + 1.) * self.image_size_scaled()
     }
 
     pub fn end_y_point(&self) -> f32 {
-        self.start_y_point + (self.last_element as f32 + 1.) * self.image_size_scaled()
+        self.start_y_point + (LAST_ELEMENT as f32 + 1.) * self.image_size_scaled()
     }
 
     //todo move to separate  struct DiscardTray
@@ -126,10 +171,10 @@ impl Board {
 
         let y_coordinate = discard_start_y_offset
             + direction_coefficient
-                * (element_num / self.last_element) as f32
+                * (element_num / LAST_ELEMENT) as f32
                 * self.discard_image_size_scaled();
         let x_coordinate = self.start_x_point
-            + (element_num % self.last_element) as f32 * self.discard_image_size_scaled();
+            + (element_num % LAST_ELEMENT) as f32 * self.discard_image_size_scaled();
 
         return (x_coordinate, y_coordinate);
     }
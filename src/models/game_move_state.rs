@@ -0,0 +1,70 @@
+use std::ops::{Deref, DerefMut};
+
+use bevy::prelude::*;
+use chess_engine::{
+    chess_move::ChessMove, chess_move_state::MoveState, chess_piece::ChessPiece,
+    common_chess::CellPosition,
+};
+
+// rules state of the game together with the selection and animation of the move on screen,
+// reads and updates of the rules state go through to the engine MoveState
+#[derive(Default, Clone, Resource)]
+pub struct GameMoveState {
+    pub rules: MoveState,
+    pub selected_piece: Option<Entity>,
+    pub selected_cell: Option<Entity>,
+    pub move_in_action: bool,
+    // rook entity and its target cell, moved together with the king while castling
+    pub castling_rook: Option<(Entity, CellPosition)>,
+}
+
+impl GameMoveState {
+    pub fn new(rules: MoveState) -> GameMoveState {
+        GameMoveState { rules, ..default() }
+    }
+
+    pub fn next_move(&mut self) {
+        self.end_move_animation();
+        self.rules.next_move();
+    }
+
+    // a move can't be taken back while it is still animated
+    pub fn undo_move(&mut self, pieces: &mut Vec<ChessPiece>) -> Option<ChessMove> {
+        if self.move_in_action {
+            return None;
+        }
+        let chess_move = self.rules.undo_move(pieces)?;
+        self.end_move_animation();
+        Some(chess_move)
+    }
+
+    pub fn redo_move(&mut self, pieces: &mut Vec<ChessPiece>) -> Option<ChessMove> {
+        if self.move_in_action {
+            return None;
+        }
+        let chess_move = self.rules.redo_move(pieces)?;
+        self.end_move_animation();
+        Some(chess_move)
+    }
+
+    pub fn end_move_animation(&mut self) {
+        self.move_in_action = false;
+        self.selected_cell = None;
+        self.selected_piece = None;
+        self.castling_rook = None;
+    }
+}
+
+impl Deref for GameMoveState {
+    type Target = MoveState;
+
+    fn deref(&self) -> &MoveState {
+        &self.rules
+    }
+}
+
+impl DerefMut for GameMoveState {
+    fn deref_mut(&mut self) -> &mut MoveState {
+        &mut self.rules
+    }
+}
@@ -1,7 +1,8 @@
 pub mod app_state;
+pub mod board_piece;
 pub mod chess_cell;
-pub mod chess_move_state;
-pub mod chess_piece;
-pub mod common_chess;
+pub mod clock;
 pub mod common_resources;
+pub mod game_move_state;
 pub mod removed_chess_piece;
+pub mod save_game;
@@ -1,14 +1,20 @@
 use bevy::prelude::Component;
-
-use super::{
+use chess_engine::{
     chess_piece::{ChessPiece, PieceType},
     common_chess::ChessColor,
 };
 
+// chess_piece.pos is the cell the captured piece stood on,
+// which differs from the move destination for en passant
 pub struct ChessPieceRemovedEvent {
     pub chess_piece: ChessPiece,
 }
 
+// sent by undo for a captured piece put back on the board
+pub struct ChessPieceRestoredEvent {
+    pub chess_piece: ChessPiece,
+}
+
 #[derive(Component)]
 pub struct RemovedChessPiece {
     pub color: ChessColor,
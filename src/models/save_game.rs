@@ -0,0 +1,469 @@
+use std::{
+    fmt, fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use chess_engine::{
+    chess_move_state::{DrawReason, MoveState},
+    chess_piece::ChessPiece,
+    common_chess::ChessColor,
+    fen_parser::FenParser,
+    notation::Notation,
+    pgn_parser::{PgnParseError, PgnParser},
+};
+
+use super::clock::{Clock, ClockPreset};
+
+const SAVE_DIRECTORY: &str = "saves";
+// slot written whenever the game screen is left, Continue goes on with it
+pub const AUTOSAVE_SLOT: usize = 0;
+// slots picked on the save screen, numbered from 1
+pub const SAVE_SLOT_COUNT: usize = 5;
+
+#[derive(Debug)]
+pub enum SaveGameError {
+    Io(io::Error),
+    InvalidPgn(PgnParseError),
+    InvalidTag(&'static str),
+}
+
+impl fmt::Display for SaveGameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveGameError::Io(err) => write!(f, "save file i/o failed: {}", err),
+            SaveGameError::InvalidPgn(err) => write!(f, "invalid save file: {}", err),
+            SaveGameError::InvalidTag(tag) => write!(f, "invalid save file tag {}", tag),
+        }
+    }
+}
+
+impl std::error::Error for SaveGameError {}
+
+impl From<io::Error> for SaveGameError {
+    fn from(err: io::Error) -> Self {
+        SaveGameError::Io(err)
+    }
+}
+
+// everything needed to go on with a game: the position with its move history and
+// undone moves, clocks and players; the captured pieces follow from the history.
+// It is stored as PGN with extra tags so that other chess programs can open it
+#[derive(Clone)]
+pub struct SaveGame {
+    // name shown on the slot, the PGN Event
+    pub name: String,
+    // unix time in seconds
+    pub saved_at: u64,
+    pub pieces: Vec<ChessPiece>,
+    pub move_state: MoveState,
+    pub clock: Clock,
+    pub computer_color: Option<ChessColor>,
+    pub player_color: ChessColor,
+}
+
+impl SaveGame {
+    pub fn to_pgn(&self) -> String {
+        let mut move_state = self.move_state.clone();
+        // a pawn move still waiting for its promotion piece has no SAN yet, the position
+        // before it is saved
+        if move_state.promotion_cell.take().is_some() {
+            move_state.move_history.pop();
+        }
+        if move_state.start_fen.is_none() {
+            let pieces: Vec<&ChessPiece> = self.pieces.iter().collect();
+            move_state.start_fen = Some(FenParser::save_fen(&pieces, &move_state));
+        }
+        // the movetext is the game as played, undone moves are kept in the Redo tag
+        let redo_moves: Vec<String> = move_state
+            .redo_history
+            .iter()
+            .rev()
+            .map(|chess_move| {
+                Notation::move_to_uci(&chess_move.piece.pos, &chess_move.to, &chess_move.promotion)
+            })
+            .collect();
+
+        let (white, black) = SaveGame::players(&self.computer_color);
+        let saved_at = UNIX_EPOCH + Duration::from_secs(self.saved_at);
+        let mut tags = vec![
+            ("Event", self.name.clone()),
+            ("White", white.to_string()),
+            ("Black", black.to_string()),
+            ("Saved", self.saved_at.to_string()),
+            (
+                "PlayerColor",
+                SaveGame::color_tag(&self.player_color).to_string(),
+            ),
+            (
+                "Termination",
+                SaveGame::termination(&move_state).to_string(),
+            ),
+            ("Clock", self.clock.preset.to_string()),
+            (
+                "WhiteClock",
+                self.clock
+                    .remaining(&ChessColor::WHITE)
+                    .as_millis()
+                    .to_string(),
+            ),
+            (
+                "BlackClock",
+                self.clock
+                    .remaining(&ChessColor::BLACK)
+                    .as_millis()
+                    .to_string(),
+            ),
+        ];
+        if let Some(color) = &self.computer_color {
+            tags.push(("ComputerColor", SaveGame::color_tag(color).to_string()));
+        }
+        if !redo_moves.is_empty() {
+            tags.push(("Redo", redo_moves.join(" ")));
+        }
+        PgnParser::save_pgn_with_tags(&move_state, &PgnParser::pgn_date(saved_at), &tags)
+    }
+
+    pub fn from_pgn(pgn: &str) -> Result<SaveGame, SaveGameError> {
+        let game = PgnParser::parse_pgn(pgn).map_err(SaveGameError::InvalidPgn)?;
+        let tag = |name: &'static str| game.tag(name).ok_or(SaveGameError::InvalidTag(name));
+        let number = |name: &'static str| -> Result<u64, SaveGameError> {
+            tag(name)?
+                .parse()
+                .map_err(|_| SaveGameError::InvalidTag(name))
+        };
+        let color = |name: &'static str| -> Result<ChessColor, SaveGameError> {
+            SaveGame::parse_color_tag(tag(name)?).ok_or(SaveGameError::InvalidTag(name))
+        };
+
+        let (mut pieces, mut move_state) =
+            PgnParser::replay(&game, None).map_err(SaveGameError::InvalidPgn)?;
+
+        // a resignation, an agreed draw or a lost time is not seen from the moves
+        if !move_state.is_game_over() {
+            match (game.tag("Result"), game.tag("Termination")) {
+                (Some("1-0"), Some("time forfeit")) => {
+                    move_state.flag_state = Some(ChessColor::BLACK)
+                }
+                (Some("0-1"), Some("time forfeit")) => {
+                    move_state.flag_state = Some(ChessColor::WHITE)
+                }
+                (Some("1-0"), _) => move_state.resign_state = Some(ChessColor::BLACK),
+                (Some("0-1"), _) => move_state.resign_state = Some(ChessColor::WHITE),
+                (Some("1/2-1/2"), _) => move_state.draw_state = Some(DrawReason::Agreement),
+                _ => {}
+            }
+        }
+
+        // undone moves are made after the played ones and taken back again
+        let redo_moves: Vec<&str> = game
+            .tag("Redo")
+            .map_or(Vec::new(), |redo| redo.split_whitespace().collect());
+        for uci in redo_moves.iter() {
+            let (from, to, promotion) =
+                Notation::move_from_uci(uci).ok_or(SaveGameError::InvalidTag("Redo"))?;
+            if move_state.is_game_over()
+                || !move_state.is_complete_move(&pieces, &from, &to, &promotion)
+            {
+                return Err(SaveGameError::InvalidTag("Redo"));
+            }
+            move_state.make_move(&mut pieces, &from, &to, promotion);
+            move_state.next_move();
+        }
+        for _ in redo_moves.iter() {
+            move_state.undo_move(&mut pieces);
+        }
+
+        let preset = ClockPreset::parse(tag("Clock")?).ok_or(SaveGameError::InvalidTag("Clock"))?;
+        let clock = Clock::restore(
+            preset,
+            Duration::from_millis(number("WhiteClock")?),
+            Duration::from_millis(number("BlackClock")?),
+        );
+        let computer_color = match game.tag("ComputerColor") {
+            Some(_) => Some(color("ComputerColor")?),
+            None => None,
+        };
+
+        Ok(SaveGame {
+            name: tag("Event")?.to_string(),
+            saved_at: number("Saved")?,
+            pieces,
+            move_state,
+            clock,
+            computer_color,
+            player_color: color("PlayerColor")?,
+        })
+    }
+
+    pub fn write(&self, slot: usize) -> Result<(), SaveGameError> {
+        fs::create_dir_all(SAVE_DIRECTORY)?;
+        fs::write(SaveGame::slot_path(slot), self.to_pgn())?;
+        Ok(())
+    }
+
+    pub fn read(slot: usize) -> Result<SaveGame, SaveGameError> {
+        SaveGame::from_pgn(&fs::read_to_string(SaveGame::slot_path(slot))?)
+    }
+
+    pub fn fen(&self) -> String {
+        let pieces: Vec<&ChessPiece> = self.pieces.iter().collect();
+        FenParser::save_fen(&pieces, &self.move_state)
+    }
+
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs())
+    }
+
+    // "YYYY.MM.DD hh:mm" in UTC
+    pub fn saved_at_label(&self) -> String {
+        let time = UNIX_EPOCH + Duration::from_secs(self.saved_at);
+        format!(
+            "{} {:02}:{:02}",
+            PgnParser::pgn_date(time),
+            self.saved_at / 3600 % 24,
+            self.saved_at / 60 % 60
+        )
+    }
+
+    // "White - Black" of the game, the name of a game saved without one
+    pub fn default_name(computer_color: &Option<ChessColor>) -> String {
+        let (white, black) = SaveGame::players(computer_color);
+        format!("{} - {}", white, black)
+    }
+
+    fn players(computer_color: &Option<ChessColor>) -> (&'static str, &'static str) {
+        match computer_color {
+            None => ("Player", "Player"),
+            Some(ChessColor::WHITE) => ("Computer", "Player"),
+            Some(ChessColor::BLACK) => ("Player", "Computer"),
+        }
+    }
+
+    fn slot_path(slot: usize) -> PathBuf {
+        let name = match slot {
+            AUTOSAVE_SLOT => "autosave.pgn".to_string(),
+            _ => format!("slot_{}.pgn", slot),
+        };
+        PathBuf::from(SAVE_DIRECTORY).join(name)
+    }
+
+    // standard PGN Termination of the game
+    fn termination(move_state: &MoveState) -> &'static str {
+        if move_state.flag_state.is_some() {
+            "time forfeit"
+        } else if move_state.is_game_over() {
+            "normal"
+        } else {
+            "unterminated"
+        }
+    }
+
+    fn color_tag(color: &ChessColor) -> &'static str {
+        match color {
+            ChessColor::WHITE => "white",
+            ChessColor::BLACK => "black",
+        }
+    }
+
+    fn parse_color_tag(value: &str) -> Option<ChessColor> {
+        match value {
+            "white" => Some(ChessColor::WHITE),
+            "black" => Some(ChessColor::BLACK),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod run_tests {
+    use chess_engine::fen_parser::START_POSITION_FEN;
+
+    use super::*;
+
+    fn played_game(moves: &[(&str, &str)]) -> (Vec<ChessPiece>, MoveState) {
+        let (mut pieces, mut move_state) = FenParser::parse_fen(START_POSITION_FEN).unwrap();
+        for (from, to) in moves {
+            let from = Notation::cell_from_string(from).unwrap();
+            let to = Notation::cell_from_string(to).unwrap();
+            move_state.make_move(&mut pieces, &from, &to, None).unwrap();
+            move_state.next_move();
+        }
+        (pieces, move_state)
+    }
+
+    #[test]
+    fn test_save_game_round_trip() {
+        let (mut pieces, mut move_state) =
+            played_game(&[("e2", "e4"), ("d7", "d5"), ("e4", "d5"), ("g8", "f6")]);
+        move_state.undo_move(&mut pieces);
+        let save_game = SaveGame {
+            name: "Evening game".to_string(),
+            saved_at: 1_700_000_000,
+            pieces,
+            move_state,
+            clock: Clock::restore(
+                ClockPreset::BLITZ,
+                Duration::from_millis(170_500),
+                Duration::from_millis(95_000),
+            ),
+            computer_color: Some(ChessColor::BLACK),
+            player_color: ChessColor::WHITE,
+        };
+
+        let pgn = save_game.to_pgn();
+        assert!(pgn.contains("[Event \"Evening game\"]\n"));
+        assert!(pgn.contains("[White \"Player\"]\n[Black \"Computer\"]\n"));
+        assert!(pgn.contains("[Redo \"g8f6\"]\n"));
+        assert!(pgn.contains("1. e4 d5 2. exd5 *"));
+
+        let loaded = SaveGame::from_pgn(&pgn).unwrap();
+        assert_eq!("Evening game", loaded.name);
+        assert_eq!(save_game.saved_at, loaded.saved_at);
+        assert_eq!(save_game.fen(), loaded.fen());
+        assert_eq!(3, loaded.move_state.move_history.len());
+        assert_eq!("Nf6", loaded.move_state.redo_history[0].san);
+        assert_eq!(
+            Some(ChessColor::WHITE),
+            loaded.move_state.move_history[2]
+                .captured_piece
+                .as_ref()
+                .map(|piece| piece.color.opposite())
+        );
+        assert_eq!(ClockPreset::BLITZ, loaded.clock.preset);
+        assert_eq!(
+            Duration::from_millis(170_500),
+            loaded.clock.remaining(&ChessColor::WHITE)
+        );
+        assert_eq!(
+            Duration::from_millis(95_000),
+            loaded.clock.remaining(&ChessColor::BLACK)
+        );
+        assert_eq!(Some(ChessColor::BLACK), loaded.computer_color);
+        assert_eq!(ChessColor::WHITE, loaded.player_color);
+        assert_eq!("2023.11.14 22:13", loaded.saved_at_label());
+    }
+
+    #[test]
+    fn test_save_game_from_position() {
+        let fen = "4k3/8/8/8/8/8/4P3/4K3 b - - 0 12";
+        let (pieces, move_state) = FenParser::parse_fen(fen).unwrap();
+        let save_game = SaveGame {
+            name: SaveGame::default_name(&None),
+            saved_at: 0,
+            pieces,
+            move_state,
+            clock: Clock::default(),
+            computer_color: None,
+            player_color: ChessColor::BLACK,
+        };
+        let loaded = SaveGame::from_pgn(&save_game.to_pgn()).unwrap();
+        assert_eq!(fen, loaded.fen());
+        assert!(!loaded.clock.is_timed());
+        assert_eq!(None, loaded.computer_color);
+    }
+
+    #[test]
+    fn test_save_game_invalid_tags() {
+        let (pieces, move_state) = played_game(&[("e2", "e4")]);
+        let pgn = SaveGame {
+            name: SaveGame::default_name(&None),
+            saved_at: 0,
+            pieces,
+            move_state,
+            clock: Clock::default(),
+            computer_color: None,
+            player_color: ChessColor::WHITE,
+        }
+        .to_pgn();
+
+        for redo in ["e2e4", "e7e5 e7e5", "e7"] {
+            let wrong_redo = pgn.replace("\n\n1. e4", &format!("\n[Redo \"{}\"]\n\n1. e4", redo));
+            assert!(matches!(
+                SaveGame::from_pgn(&wrong_redo),
+                Err(SaveGameError::InvalidTag("Redo"))
+            ));
+        }
+        let no_clock = pgn.replace("[Clock \"unlimited\"]\n", "");
+        assert!(matches!(
+            SaveGame::from_pgn(&no_clock),
+            Err(SaveGameError::InvalidTag("Clock"))
+        ));
+    }
+
+    #[test]
+    fn test_save_game_pending_promotion() {
+        let fen = "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1";
+        let (mut pieces, mut move_state) = FenParser::parse_fen(fen).unwrap();
+        for (from, to) in [("e1", "d1"), ("e8", "f7")] {
+            let from = Notation::cell_from_string(from).unwrap();
+            let to = Notation::cell_from_string(to).unwrap();
+            move_state.make_move(&mut pieces, &from, &to, None).unwrap();
+            move_state.next_move();
+        }
+        let piece_refs: Vec<&ChessPiece> = pieces.iter().collect();
+        let fen_before_pawn_move = FenParser::save_fen(&piece_refs, &move_state);
+        let from = Notation::cell_from_string("b7").unwrap();
+        let to = Notation::cell_from_string("b8").unwrap();
+        move_state.make_move(&mut pieces, &from, &to, None).unwrap();
+        assert!(move_state.promotion_cell.is_some());
+
+        let pgn = SaveGame {
+            name: SaveGame::default_name(&None),
+            saved_at: 0,
+            pieces,
+            move_state,
+            clock: Clock::default(),
+            computer_color: None,
+            player_color: ChessColor::WHITE,
+        }
+        .to_pgn();
+        assert!(pgn.contains("1. Kd1 Kf7 *"));
+        let loaded = SaveGame::from_pgn(&pgn).unwrap();
+        assert_eq!(fen_before_pawn_move, loaded.fen());
+        assert_eq!(2, loaded.move_state.move_history.len());
+        assert_eq!(None, loaded.move_state.promotion_cell);
+    }
+
+    #[test]
+    fn test_save_game_termination() {
+        let save_game = |move_state: MoveState, pieces: Vec<ChessPiece>| SaveGame {
+            name: SaveGame::default_name(&None),
+            saved_at: 0,
+            pieces,
+            move_state,
+            clock: Clock::default(),
+            computer_color: None,
+            player_color: ChessColor::WHITE,
+        };
+
+        let (pieces, mut move_state) = played_game(&[("e2", "e4"), ("e7", "e5")]);
+        move_state.resign_state = Some(ChessColor::WHITE);
+        let pgn = save_game(move_state, pieces).to_pgn();
+        assert!(pgn.contains("[Result \"0-1\"]\n"));
+        assert!(pgn.contains("[Termination \"normal\"]\n"));
+        let loaded = SaveGame::from_pgn(&pgn).unwrap();
+        assert_eq!(Some(ChessColor::WHITE), loaded.move_state.resign_state);
+        assert!(loaded.move_state.is_game_over());
+
+        let (pieces, mut move_state) = played_game(&[("e2", "e4")]);
+        move_state.draw_state = Some(DrawReason::Agreement);
+        let loaded = SaveGame::from_pgn(&save_game(move_state, pieces).to_pgn()).unwrap();
+        assert_eq!(Some(DrawReason::Agreement), loaded.move_state.draw_state);
+        assert_eq!(None, loaded.move_state.resign_state);
+
+        let (pieces, mut move_state) = played_game(&[("e2", "e4")]);
+        move_state.flag_state = Some(ChessColor::BLACK);
+        let pgn = save_game(move_state, pieces).to_pgn();
+        assert!(pgn.contains("[Termination \"time forfeit\"]\n"));
+        let loaded = SaveGame::from_pgn(&pgn).unwrap();
+        assert_eq!(Some(ChessColor::BLACK), loaded.move_state.flag_state);
+        assert_eq!(None, loaded.move_state.resign_state);
+
+        let (pieces, move_state) = played_game(&[("e2", "e4")]);
+        let loaded = SaveGame::from_pgn(&save_game(move_state, pieces).to_pgn()).unwrap();
+        assert!(!loaded.move_state.is_game_over());
+    }
+}
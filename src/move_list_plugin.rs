@@ -0,0 +1,373 @@
+use bevy::prelude::*;
+use chess_engine::{chess_move::ChessMove, chess_piece::ChessPiece};
+
+use crate::{
+    assets_helper::AssetsHelper,
+    chess_board_plugin::PIECES_Z,
+    models::{
+        app_state::{AppState, BrowseState, PromotionState},
+        board_piece::BoardPiece,
+        common_resources::{Board, BrowsedPosition, FontHolder},
+        game_move_state::GameMoveState,
+    },
+    titles::Titles,
+    ui_menu_plugin::NORMAL_BUTTON,
+};
+
+// full moves listed at once, the rows around the shown position
+const MOVE_LIST_ROWS: usize = 16;
+
+// number of played moves of the position to show, None for the latest one
+#[derive(Default, Resource)]
+struct MoveBrowser {
+    target: Option<usize>,
+    // shown position with the played and all moves of the game it was built for
+    shown: Option<(usize, usize, usize)>,
+}
+
+// sprite of a piece of the browsed position, the game's own pieces are hidden meanwhile
+#[derive(Component)]
+struct BrowsedPiece;
+
+// rows of the list, rebuilt once the moves or the shown position change
+#[derive(Component)]
+struct MoveListPanel {
+    // played, shown and all moves the rows were built for
+    shown: Option<(usize, usize, usize, String)>,
+}
+
+#[derive(Component)]
+struct MoveListTitle;
+
+// number of played moves after a click on the move
+#[derive(Component)]
+struct MoveListButton(usize);
+
+#[derive(Component)]
+struct OnMoveListScreen;
+
+pub struct MoveListPlugin;
+
+impl Plugin for MoveListPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<BrowseState>()
+            .insert_resource(MoveBrowser::default())
+            .add_system(setup_move_list.in_schedule(OnEnter(AppState::Game)))
+            .add_systems(
+                (despawn_move_list, reset_browse_state).in_schedule(OnExit(AppState::Game)),
+            )
+            .add_systems(
+                (
+                    handle_move_list_input,
+                    show_browsed_position_system,
+                    update_move_list_system,
+                )
+                    .chain()
+                    .distributive_run_if(in_state(PromotionState::Inactive))
+                    .in_set(OnUpdate(AppState::Game)),
+            )
+            .add_system(hide_board_pieces_system.in_set(OnUpdate(AppState::Game)));
+    }
+}
+
+fn setup_move_list(mut commands: Commands, font_holder: Res<FontHolder>, titles: Res<Titles>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(OnMoveListScreen)
+        .with_children(|node| {
+            node.spawn(TextBundle::from_section(
+                titles.move_list.clone(),
+                TextStyle {
+                    font: font_holder.font.clone(),
+                    font_size: 20.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ))
+            .insert(MoveListTitle);
+            node.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(MoveListPanel { shown: None });
+        });
+}
+
+fn despawn_move_list(mut commands: Commands, q_despawn: Query<Entity, With<OnMoveListScreen>>) {
+    for entity in q_despawn.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn reset_browse_state(
+    mut commands: Commands,
+    mut move_browser: ResMut<MoveBrowser>,
+    mut next_state: ResMut<NextState<BrowseState>>,
+) {
+    *move_browser = MoveBrowser::default();
+    commands.remove_resource::<BrowsedPosition>();
+    next_state.set(BrowseState::Latest);
+}
+
+// a click on a move or the arrow keys pick the position, Home the start and End the latest one
+fn handle_move_list_input(
+    keys: Res<Input<KeyCode>>,
+    q_interaction: Query<(&Interaction, &MoveListButton), Changed<Interaction>>,
+    move_state: Res<GameMoveState>,
+    mut move_browser: ResMut<MoveBrowser>,
+) {
+    let played = move_state.move_history.len();
+    let latest = played + move_state.redo_history.len();
+    let current = move_browser.target.unwrap_or(played);
+    let clicked = q_interaction
+        .iter()
+        .find(|(interaction, _)| Interaction::Clicked.eq(interaction))
+        .map(|(_, button)| button.0);
+    let target = if clicked.is_some() {
+        clicked
+    } else if keys.just_pressed(KeyCode::Left) {
+        Some(current.saturating_sub(1))
+    } else if keys.just_pressed(KeyCode::Right) {
+        Some((current + 1).min(latest))
+    } else if keys.just_pressed(KeyCode::Home) {
+        Some(0)
+    } else if keys.just_pressed(KeyCode::End) {
+        Some(latest)
+    } else {
+        None
+    };
+    if target.is_some() {
+        move_browser.target = target;
+    }
+}
+
+// builds the picked position from a copy of the game, the game itself goes on untouched
+#[allow(clippy::too_many_arguments)]
+fn show_browsed_position_system(
+    mut commands: Commands,
+    move_state: Res<GameMoveState>,
+    mut move_browser: ResMut<MoveBrowser>,
+    q_chess_piece: Query<&BoardPiece>,
+    q_browsed_piece: Query<Entity, With<BrowsedPiece>>,
+    mut next_state: ResMut<NextState<BrowseState>>,
+    browse_state: Res<State<BrowseState>>,
+    board: Res<Board>,
+    assets: Res<AssetServer>,
+) {
+    // the pieces on the board match the game once a made move is finished
+    if move_state.move_in_action || move_state.promotion_cell.is_some() {
+        return;
+    }
+    let played = move_state.move_history.len();
+    let latest = played + move_state.redo_history.len();
+    let target = move_browser
+        .target
+        .filter(|target| *target != played && *target <= latest);
+    let shown = target.map(|target| (target, played, latest));
+    if move_browser.shown == shown {
+        return;
+    }
+    move_browser.target = target;
+    move_browser.shown = shown;
+
+    for entity in q_browsed_piece.iter() {
+        commands.entity(entity).despawn();
+    }
+    let browse = match target {
+        None => {
+            commands.remove_resource::<BrowsedPosition>();
+            BrowseState::Latest
+        }
+        Some(target) => {
+            let mut pieces: Vec<ChessPiece> = q_chess_piece.iter().map(|cp| cp.0.clone()).collect();
+            let mut browsed_state = move_state.rules.clone();
+            while browsed_state.move_history.len() > target
+                && browsed_state.undo_move(&mut pieces).is_some()
+            {}
+            while browsed_state.move_history.len() < target
+                && browsed_state.redo_move(&mut pieces).is_some()
+            {}
+            for piece in pieces.iter() {
+                let (x, y) = board.coordinates(&piece.pos);
+                commands
+                    .spawn(SpriteBundle {
+                        texture: AssetsHelper::load_piece_image(
+                            &piece.color,
+                            &piece.piece_type,
+                            &assets,
+                        ),
+                        transform: Transform {
+                            translation: Vec3::new(x, y, PIECES_Z),
+                            scale: Vec3::splat(board.image_scale),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(BrowsedPiece)
+                    .insert(OnMoveListScreen);
+            }
+            commands.insert_resource(BrowsedPosition {
+                pieces,
+                move_state: browsed_state,
+            });
+            BrowseState::Browsing
+        }
+    };
+    if browse_state.0 != browse {
+        next_state.set(browse);
+    }
+}
+
+fn hide_board_pieces_system(
+    browse_state: Res<State<BrowseState>>,
+    mut q_visibility: Query<&mut Visibility, With<BoardPiece>>,
+) {
+    let visibility = match browse_state.0 {
+        BrowseState::Latest => Visibility::Inherited,
+        BrowseState::Browsing => Visibility::Hidden,
+    };
+    for mut piece_visibility in q_visibility.iter_mut() {
+        if *piece_visibility != visibility {
+            *piece_visibility = visibility;
+        }
+    }
+}
+
+// numbered pairs of SAN moves, the shown position is marked and taken back moves are dimmed
+#[allow(clippy::too_many_arguments)]
+fn update_move_list_system(
+    mut commands: Commands,
+    move_state: Res<GameMoveState>,
+    move_browser: Res<MoveBrowser>,
+    browse_state: Res<State<BrowseState>>,
+    font_holder: Res<FontHolder>,
+    titles: Res<Titles>,
+    mut q_panel: Query<(Entity, &mut MoveListPanel)>,
+    mut q_title: Query<&mut Text, With<MoveListTitle>>,
+) {
+    for mut text in q_title.iter_mut() {
+        text.sections[0].value = match browse_state.0 {
+            BrowseState::Latest => titles.move_list.clone(),
+            BrowseState::Browsing => titles.browsing.clone(),
+        };
+    }
+
+    let played = move_state.move_history.len();
+    let moves: Vec<&ChessMove> = move_state
+        .move_history
+        .iter()
+        .chain(move_state.redo_history.iter().rev())
+        .collect();
+    // a promotion gets its piece letter after the move was made
+    let last_san = moves.last().map(|m| m.san.clone()).unwrap_or_default();
+    let shown_moves = move_browser.target.unwrap_or(played);
+    let shown = Some((played, shown_moves, moves.len(), last_san));
+    let Ok((panel_entity, mut panel)) = q_panel.get_single_mut() else {
+        return;
+    };
+    if panel.shown == shown {
+        return;
+    }
+    panel.shown = shown;
+
+    // ply of the first move counted from the start of the game, black may move first
+    let first_ply = move_state.start_ply() as usize;
+    let row_of = |index: usize| (first_ply + index) / 2 - first_ply / 2;
+    let row_count = moves.last().map_or(0, |_| row_of(moves.len() - 1) + 1);
+    let current_row = row_of(shown_moves.saturating_sub(1));
+    let first_row = current_row
+        .saturating_sub(MOVE_LIST_ROWS / 2)
+        .min(row_count.saturating_sub(MOVE_LIST_ROWS));
+
+    let text_style = |color: Color| TextStyle {
+        font: font_holder.font.clone(),
+        font_size: 18.,
+        color,
+    };
+    let button = ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(90.0), Val::Px(26.0)),
+            margin: UiRect::all(Val::Px(1.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    };
+
+    commands.entity(panel_entity).despawn_descendants();
+    commands.entity(panel_entity).with_children(|panel| {
+        for row in first_row..(first_row + MOVE_LIST_ROWS).min(row_count) {
+            panel
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row_node| {
+                    let fullmove = first_ply / 2 + row + 1;
+                    row_node
+                        .spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(40.0), Val::Px(26.0)),
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|number| {
+                            number.spawn(TextBundle::from_section(
+                                format!("{}.", fullmove),
+                                text_style(Color::GRAY),
+                            ));
+                        });
+                    for (index, chess_move) in moves.iter().enumerate() {
+                        if row_of(index) != row {
+                            continue;
+                        }
+                        // black's first move is shown in the black column
+                        if index == 0 && !first_ply.is_multiple_of(2) {
+                            row_node.spawn(NodeBundle {
+                                style: button.style.clone(),
+                                ..default()
+                            });
+                        }
+                        let color = if index + 1 == shown_moves {
+                            Color::YELLOW
+                        } else if index >= played {
+                            Color::GRAY
+                        } else {
+                            Color::rgb(0.9, 0.9, 0.9)
+                        };
+                        row_node
+                            .spawn(button.clone())
+                            .with_children(|button| {
+                                button.spawn(TextBundle::from_section(
+                                    chess_move.san.clone(),
+                                    text_style(color),
+                                ));
+                            })
+                            .insert(MoveListButton(index + 1));
+                    }
+                });
+        }
+    });
+}
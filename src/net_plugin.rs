@@ -0,0 +1,533 @@
+use std::{env, io, net::TcpListener};
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+use chess_engine::{
+    chess_move_state::DrawReason,
+    chess_piece::ChessPiece,
+    common_chess::ChessColor,
+    fen_parser::FenParser,
+    net_peer::{NetPeer, DEFAULT_PORT},
+    net_protocol::{NetMessage, NetProtocol, NetProtocolError, PROTOCOL_VERSION},
+};
+use futures_lite::future;
+
+use crate::{
+    chess_board_plugin::start_made_move_animation,
+    models::{
+        app_state::AppState,
+        board_piece::BoardPiece,
+        chess_cell::ChessCell,
+        common_resources::{FontHolder, GameMode, GameState, LoadedGame, NetRole},
+        game_move_state::GameMoveState,
+        removed_chess_piece::ChessPieceRemovedEvent,
+    },
+    titles::Titles,
+    ui_menu_plugin::NORMAL_BUTTON,
+};
+
+const CHAT_LINES: usize = 6;
+const CHAT_MAX_LENGTH: usize = 120;
+
+// where the host listens and the joining side connects to
+#[derive(Resource)]
+pub struct NetSettings {
+    pub port: u16,
+    pub address: String,
+}
+
+impl Default for NetSettings {
+    fn default() -> Self {
+        NetSettings {
+            port: DEFAULT_PORT,
+            address: format!("127.0.0.1:{}", DEFAULT_PORT),
+        }
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Default, Clone, PartialEq, Debug)]
+pub enum NetStatus {
+    #[default]
+    WAITING,
+    CONNECTING,
+    PLAYING,
+    DISCONNECTED,
+}
+
+// connection of a network game, it is kept while the board is rebuilt for the synced game
+#[derive(Default, Resource)]
+pub struct NetConnection {
+    listener: Option<TcpListener>,
+    // connecting may take a while, so it runs on the async compute pool
+    connecting: Option<Task<io::Result<NetPeer>>>,
+    peer: Option<NetPeer>,
+    // moves of the history the peer knows about
+    synced_moves: usize,
+    is_draw_offered: bool,
+    is_draw_offered_by_peer: bool,
+    status: NetStatus,
+    // color of the author and the text
+    chat: Vec<(ChessColor, String)>,
+    chat_input: String,
+}
+
+impl NetConnection {
+    fn is_started(&self) -> bool {
+        self.listener.is_some() || self.connecting.is_some() || self.peer.is_some()
+    }
+
+    fn send(&mut self, message: &NetMessage) {
+        let Some(peer) = &self.peer else {
+            return;
+        };
+        if let Err(err) = peer.send(message) {
+            warn!("failed to send '{}': {}", message.to_line(), err);
+            self.disconnect();
+        }
+    }
+
+    // the game stays on the board, only the peer is gone
+    fn disconnect(&mut self) {
+        self.listener = None;
+        self.connecting = None;
+        self.peer = None;
+        self.status = NetStatus::DISCONNECTED;
+    }
+}
+
+#[derive(Component)]
+enum NetButton {
+    Resign,
+    OfferDraw,
+}
+
+#[derive(Component)]
+struct NetStatusText;
+
+#[derive(Component)]
+struct ChatText;
+
+#[derive(Component)]
+struct OnNetScreen;
+
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NetSettings::default())
+            .insert_resource(NetConnection::default())
+            .add_startup_system(net_settings_from_args)
+            .add_systems((start_network_game, setup_net_panel).in_schedule(OnEnter(AppState::Game)))
+            .add_system(despawn_net_panel.in_schedule(OnExit(AppState::Game)))
+            .add_system(close_network_game.in_schedule(OnEnter(AppState::MainMenu)))
+            .add_systems(
+                (
+                    connect_system,
+                    receive_message_system,
+                    send_moves_system,
+                    handle_net_buttons,
+                    chat_input_system,
+                    display_net_panel_system,
+                )
+                    .chain()
+                    .distributive_run_if(is_network_game)
+                    .in_set(OnUpdate(AppState::Game)),
+            );
+    }
+}
+
+fn is_network_game(game_mode: Res<GameMode>) -> bool {
+    game_mode.net_role.is_some()
+}
+
+// `--port <port>` to host on, `--address <host>:<port>` to join
+fn net_settings_from_args(mut settings: ResMut<NetSettings>) {
+    let args: Vec<String> = env::args().collect();
+    let arg_value = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+    if let Some(port) = arg_value("--port") {
+        match port.parse() {
+            Ok(port) => settings.port = port,
+            Err(_) => warn!("invalid port {}", port),
+        }
+    }
+    if let Some(address) = arg_value("--address") {
+        settings.address = address.clone();
+    }
+}
+
+// the board is entered again once the game is synced, the connection goes on then
+fn start_network_game(
+    game_mode: Res<GameMode>,
+    settings: Res<NetSettings>,
+    mut connection: ResMut<NetConnection>,
+) {
+    let Some(net_role) = &game_mode.net_role else {
+        return;
+    };
+    if connection.is_started() {
+        return;
+    }
+    *connection = NetConnection::default();
+    match net_role {
+        NetRole::HOST => match NetPeer::listen(settings.port) {
+            Ok(listener) => connection.listener = Some(listener),
+            Err(err) => {
+                warn!("failed to listen on port {}: {}", settings.port, err);
+                connection.disconnect();
+            }
+        },
+        NetRole::JOIN => {
+            let address = settings.address.clone();
+            let task = AsyncComputeTaskPool::get().spawn(async move { NetPeer::join(&address) });
+            connection.connecting = Some(task);
+            connection.status = NetStatus::CONNECTING;
+        }
+    }
+}
+
+fn close_network_game(mut connection: ResMut<NetConnection>, mut game_mode: ResMut<GameMode>) {
+    *connection = NetConnection::default();
+    game_mode.net_role = None;
+    game_mode.remote_color = None;
+}
+
+fn hello(color: &ChessColor) -> NetMessage {
+    NetMessage::Hello {
+        version: PROTOCOL_VERSION,
+        color: color.clone(),
+    }
+}
+
+// the host says hello with the game on its board as soon as the peer joins
+fn connect_system(
+    mut connection: ResMut<NetConnection>,
+    game_mode: Res<GameMode>,
+    move_state: Res<GameMoveState>,
+    q_chess_piece: Query<&BoardPiece>,
+) {
+    if let Some(listener) = &connection.listener {
+        match NetPeer::try_accept(listener) {
+            Ok(Some(peer)) => {
+                connection.listener = None;
+                connection.peer = Some(peer);
+                connection.status = NetStatus::CONNECTING;
+                let pieces: Vec<ChessPiece> = q_chess_piece.iter().map(|cp| cp.0.clone()).collect();
+                connection.synced_moves = move_state.move_history.len();
+                connection.send(&hello(&game_mode.player_color));
+                connection.send(&NetProtocol::sync_message(&pieces, &move_state));
+            }
+            Ok(None) => {}
+            Err(err) => {
+                warn!("failed to accept the peer: {}", err);
+                connection.disconnect();
+            }
+        }
+    }
+
+    let Some(task) = connection.connecting.as_mut() else {
+        return;
+    };
+    let Some(joined) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    connection.connecting = None;
+    match joined {
+        Ok(peer) => connection.peer = Some(peer),
+        Err(err) => {
+            warn!("failed to join the game: {}", err);
+            connection.disconnect();
+        }
+    }
+}
+
+// handles the messages of the peer, a move is animated before the next message is read
+#[allow(clippy::too_many_arguments)]
+fn receive_message_system(
+    mut connection: ResMut<NetConnection>,
+    mut game_mode: ResMut<GameMode>,
+    mut move_state: ResMut<GameMoveState>,
+    mut loaded_game: ResMut<LoadedGame>,
+    mut game_state: ResMut<GameState>,
+    mut app_state: ResMut<NextState<AppState>>,
+    assets: Res<AssetServer>,
+    q_chess_cell: Query<(Entity, &ChessCell)>,
+    mut q_chess_piece: Query<(Entity, &mut BoardPiece, &mut Handle<Image>)>,
+    mut piece_taken_event_writer: EventWriter<ChessPieceRemovedEvent>,
+) {
+    while !move_state.move_in_action && move_state.promotion_cell.is_none() {
+        let Some(received) = connection.peer.as_ref().and_then(NetPeer::try_receive) else {
+            return;
+        };
+        let message = match received {
+            Ok(message) => message,
+            Err(err) => {
+                warn!("network game stopped: {}", err);
+                connection.disconnect();
+                return;
+            }
+        };
+
+        let handled = match (&message, game_mode.remote_color.clone()) {
+            (NetMessage::Hello { .. }, None) => {
+                // the joining side plays the color the host left
+                let local_color = match game_mode.net_role {
+                    Some(NetRole::HOST) => Some(game_mode.player_color.clone()),
+                    _ => None,
+                };
+                NetProtocol::check_hello(&message, local_color.as_ref()).map(|remote_color| {
+                    game_mode.player_color = remote_color.opposite();
+                    game_mode.remote_color = Some(remote_color);
+                    if local_color.is_none() {
+                        connection.send(&hello(&game_mode.player_color));
+                    } else {
+                        connection.status = NetStatus::PLAYING;
+                    }
+                })
+            }
+            (NetMessage::SyncState { .. }, Some(_)) => {
+                NetProtocol::apply_sync(&message).map(|(pieces, synced_move_state)| {
+                    // the board is set up again from the synced game
+                    let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+                    loaded_game.fen = Some(FenParser::save_fen(&pieces, &synced_move_state));
+                    connection.synced_moves = synced_move_state.move_history.len();
+                    loaded_game.move_state = Some(synced_move_state);
+                    loaded_game.clock = None;
+                    *game_state = GameState::LOAD;
+                    app_state.set(AppState::Game);
+                    connection.status = NetStatus::PLAYING;
+                })
+            }
+            (NetMessage::Move { .. }, Some(remote_color)) => {
+                let mut pieces: Vec<ChessPiece> = q_chess_piece
+                    .iter()
+                    .map(|(_, cp, _)| cp.0.clone())
+                    .collect();
+                NetProtocol::apply_move(&mut pieces, &mut move_state, &remote_color, &message).map(
+                    |chess_move| {
+                        connection.synced_moves += 1;
+                        connection.is_draw_offered = false;
+                        connection.is_draw_offered_by_peer = false;
+                        start_made_move_animation(
+                            &mut move_state,
+                            chess_move,
+                            &q_chess_cell,
+                            &mut q_chess_piece,
+                            &assets,
+                            &mut piece_taken_event_writer,
+                        );
+                    },
+                )
+            }
+            (NetMessage::Resign, Some(remote_color)) => {
+                if !move_state.is_game_over() {
+                    move_state.resign_state = Some(remote_color);
+                }
+                Ok(())
+            }
+            (NetMessage::DrawOffer, Some(_)) => {
+                if !connection.is_draw_offered {
+                    connection.is_draw_offered_by_peer = true;
+                } else if !move_state.is_game_over() {
+                    move_state.draw_state = Some(DrawReason::Agreement);
+                }
+                Ok(())
+            }
+            (NetMessage::Chat(text), Some(remote_color)) => {
+                connection.chat.push((remote_color, text.clone()));
+                Ok(())
+            }
+            _ => Err(NetProtocolError::UnexpectedMessage(message.to_line())),
+        };
+        if let Err(err) = handled {
+            warn!("network game stopped: {}", err);
+            connection.disconnect();
+            return;
+        }
+        // the synced game is read on the new board
+        if matches!(message, NetMessage::SyncState { .. }) {
+            return;
+        }
+    }
+}
+
+// sends the moves made at this board, a promotion once its piece is picked
+fn send_moves_system(mut connection: ResMut<NetConnection>, move_state: Res<GameMoveState>) {
+    if connection.peer.is_none() || move_state.promotion_cell.is_some() {
+        return;
+    }
+    while let Some(message) = NetProtocol::move_message(&move_state, connection.synced_moves) {
+        connection.send(&message);
+        connection.synced_moves += 1;
+        connection.is_draw_offered = false;
+        connection.is_draw_offered_by_peer = false;
+    }
+}
+
+fn handle_net_buttons(
+    q_interaction: Query<(&Interaction, &NetButton), Changed<Interaction>>,
+    mut connection: ResMut<NetConnection>,
+    game_mode: Res<GameMode>,
+    mut move_state: ResMut<GameMoveState>,
+) {
+    let Some(remote_color) = &game_mode.remote_color else {
+        return;
+    };
+    for (interaction, button) in q_interaction.iter() {
+        if !Interaction::Clicked.eq(interaction)
+            || connection.peer.is_none()
+            || move_state.is_game_over()
+        {
+            continue;
+        }
+        match button {
+            NetButton::Resign => {
+                move_state.resign_state = Some(remote_color.opposite());
+                connection.send(&NetMessage::Resign);
+            }
+            // an offer answering the peer's one agrees to the draw
+            NetButton::OfferDraw => {
+                if connection.is_draw_offered {
+                    continue;
+                }
+                if connection.is_draw_offered_by_peer {
+                    move_state.draw_state = Some(DrawReason::Agreement);
+                }
+                connection.is_draw_offered = true;
+                connection.send(&NetMessage::DrawOffer);
+            }
+        }
+    }
+}
+
+// typed text goes to the chat line, Enter sends it
+fn chat_input_system(
+    mut char_events: EventReader<ReceivedCharacter>,
+    mut connection: ResMut<NetConnection>,
+    game_mode: Res<GameMode>,
+) {
+    for event in char_events.iter() {
+        match event.char {
+            '\r' | '\n' => {
+                let text = connection.chat_input.trim().to_string();
+                connection.chat_input.clear();
+                if text.is_empty() || connection.peer.is_none() {
+                    continue;
+                }
+                connection.send(&NetMessage::Chat(text.clone()));
+                connection.chat.push((game_mode.player_color.clone(), text));
+            }
+            '\u{8}' => {
+                connection.chat_input.pop();
+            }
+            char if !char.is_control() && connection.chat_input.len() < CHAT_MAX_LENGTH => {
+                connection.chat_input.push(char)
+            }
+            _ => {}
+        }
+    }
+}
+
+fn setup_net_panel(
+    mut commands: Commands,
+    font_holder: Res<FontHolder>,
+    titles: Res<Titles>,
+    game_mode: Res<GameMode>,
+) {
+    if game_mode.net_role.is_none() {
+        return;
+    }
+    let button = ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+            margin: UiRect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    };
+    let text_style = TextStyle {
+        font: font_holder.font.clone(),
+        font_size: 24.,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(OnNetScreen)
+        .with_children(|node| {
+            for (net_button, title) in [
+                (NetButton::Resign, &titles.button_resign),
+                (NetButton::OfferDraw, &titles.button_offer_draw),
+            ] {
+                node.spawn(button.clone())
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(title.clone(), text_style.clone()));
+                    })
+                    .insert(net_button);
+            }
+            node.spawn(TextBundle::from_section("", text_style.clone()))
+                .insert(NetStatusText);
+            node.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 18.,
+                    ..text_style.clone()
+                },
+            ))
+            .insert(ChatText);
+        });
+}
+
+fn despawn_net_panel(mut commands: Commands, q_despawn: Query<Entity, With<OnNetScreen>>) {
+    for entity in q_despawn.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn display_net_panel_system(
+    connection: Res<NetConnection>,
+    titles: Res<Titles>,
+    mut q_status_text: Query<&mut Text, (With<NetStatusText>, Without<ChatText>)>,
+    mut q_chat_text: Query<&mut Text, (With<ChatText>, Without<NetStatusText>)>,
+) {
+    if !connection.is_changed() {
+        return;
+    }
+    for mut text in q_status_text.iter_mut() {
+        text.sections[0].value = match connection.is_draw_offered_by_peer {
+            true => titles.net_draw_offered.clone(),
+            false => titles.net_status(&connection.status),
+        };
+    }
+
+    let first_line = connection.chat.len().saturating_sub(CHAT_LINES);
+    let mut lines: Vec<String> = connection.chat[first_line..]
+        .iter()
+        .map(|(color, text)| format!("{}: {}", titles.clock(color), text))
+        .collect();
+    lines.push(format!("> {}", connection.chat_input));
+    for mut text in q_chat_text.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,72 @@
+use std::{env, fs, path::PathBuf, time::SystemTime};
+
+use bevy::prelude::*;
+use chess_engine::{chess_piece::ChessPiece, fen_parser::FenParser, pgn_parser::PgnParser};
+
+use crate::models::{
+    app_state::AppState,
+    common_resources::{GameState, LoadedGame},
+    game_move_state::GameMoveState,
+};
+
+const PGN_DIRECTORY: &str = "games";
+
+pub struct PgnPlugin;
+
+impl Plugin for PgnPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_pgn_from_args.in_base_set(StartupSet::Startup))
+            .add_system(export_pgn_system.in_set(OnUpdate(AppState::Game)));
+    }
+}
+
+// `--pgn <file> [--ply <n>]` opens the game at the given ply, the last one by default
+fn load_pgn_from_args(
+    mut loaded_game: ResMut<LoadedGame>,
+    mut game_state: ResMut<GameState>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let args: Vec<String> = env::args().collect();
+    let arg_value = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|index| args.get(index + 1))
+    };
+    let Some(path) = arg_value("--pgn") else {
+        return;
+    };
+    let ply = arg_value("--ply").and_then(|ply| ply.parse().ok());
+
+    let game = fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|pgn| PgnParser::parse_pgn(&pgn).map_err(|err| err.to_string()))
+        .and_then(|game| PgnParser::replay(&game, ply).map_err(|err| err.to_string()));
+    match game {
+        Ok((pieces, move_state)) => {
+            let pieces: Vec<&ChessPiece> = pieces.iter().collect();
+            loaded_game.fen = Some(FenParser::save_fen(&pieces, &move_state));
+            loaded_game.move_state = Some(move_state);
+            *game_state = GameState::LOAD;
+            next_state.set(AppState::Game);
+        }
+        Err(err) => warn!("failed to load game from {}: {}", path, err),
+    }
+}
+
+// writes the game played so far to the games directory on F2
+fn export_pgn_system(keys: Res<Input<KeyCode>>, move_state: Res<GameMoveState>) {
+    if !keys.just_pressed(KeyCode::F2) {
+        return;
+    }
+    let now = SystemTime::now();
+    let pgn = PgnParser::save_pgn(&move_state, &PgnParser::pgn_date(now));
+    let timestamp = now
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let path = PathBuf::from(PGN_DIRECTORY).join(format!("game_{}.pgn", timestamp));
+
+    match fs::create_dir_all(PGN_DIRECTORY).and_then(|_| fs::write(&path, pgn)) {
+        Ok(_) => info!("game exported to {}", path.display()),
+        Err(err) => warn!("failed to export game to {}: {}", path.display(), err),
+    }
+}
@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+use chess_engine::chess_piece::{ChessPiece, PieceType};
+
+use crate::{
+    assets_helper::AssetsHelper,
+    models::{
+        app_state::{AppState, PromotionState},
+        board_piece::BoardPiece,
+        common_resources::FontHolder,
+        game_move_state::GameMoveState,
+    },
+    titles::Titles,
+    ui_menu_plugin::handle_ui_buttons_styles,
+};
+
+const PICKER_BACKGROUND: Color = Color::rgba(0.1, 0.1, 0.1, 0.85);
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+
+#[derive(Component)]
+struct PromotionButton(PieceType);
+
+#[derive(Component)]
+struct OnPromotionScreen;
+
+pub struct PromotionPlugin;
+
+impl Plugin for PromotionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PromotionState>()
+            .add_system(setup_promotion_picker.in_schedule(OnEnter(PromotionState::Picking)))
+            .add_system(despawn_promotion_picker.in_schedule(OnExit(PromotionState::Picking)))
+            .add_systems(
+                (despawn_promotion_picker, reset_promotion_state)
+                    .in_schedule(OnExit(AppState::Game)),
+            )
+            .add_system(
+                start_promotion
+                    .run_if(in_state(PromotionState::Inactive))
+                    .in_set(OnUpdate(AppState::Game)),
+            )
+            .add_systems(
+                (handle_ui_buttons_styles, handle_promotion_button_clicked)
+                    .distributive_run_if(in_state(AppState::Game))
+                    .in_set(OnUpdate(PromotionState::Picking)),
+            );
+    }
+}
+
+// opens the picker once the pawn reached the last rank, also after returning from the main menu
+fn start_promotion(
+    move_state: Res<GameMoveState>,
+    mut next_state: ResMut<NextState<PromotionState>>,
+) {
+    if move_state.promotion_cell.is_some() && !move_state.move_in_action {
+        next_state.set(PromotionState::Picking);
+    }
+}
+
+fn reset_promotion_state(mut next_state: ResMut<NextState<PromotionState>>) {
+    next_state.set(PromotionState::Inactive);
+}
+
+fn setup_promotion_picker(
+    mut commands: Commands,
+    font_holder: Res<FontHolder>,
+    titles: Res<Titles>,
+    assets: Res<AssetServer>,
+    move_state: Res<GameMoveState>,
+) {
+    let button = ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(80.0), Val::Px(80.0)),
+            margin: UiRect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    };
+    let image_style = Style {
+        size: Size::new(Val::Px(64.0), Val::Px(64.0)),
+        ..default()
+    };
+    let text_style = TextStyle {
+        font: font_holder.font.clone(),
+        font_size: 30.,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Auto),
+                padding: UiRect::all(Val::Px(10.0)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            background_color: PICKER_BACKGROUND.into(),
+            ..default()
+        })
+        .insert(OnPromotionScreen)
+        .with_children(|node| {
+            node.spawn(TextBundle::from_section(
+                titles.promotion.clone(),
+                text_style.clone(),
+            ));
+        })
+        .with_children(|node| {
+            node.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|row| {
+                for piece_type in [
+                    PieceType::QUEEN,
+                    PieceType::ROOK,
+                    PieceType::BISHOP,
+                    PieceType::KNIGHT,
+                ] {
+                    let image = AssetsHelper::load_piece_image(
+                        &move_state.current_collor,
+                        &piece_type,
+                        &assets,
+                    );
+                    row.spawn(button.clone())
+                        .with_children(|button| {
+                            button.spawn(ImageBundle {
+                                style: image_style.clone(),
+                                image: image.into(),
+                                ..default()
+                            });
+                        })
+                        .insert(PromotionButton(piece_type));
+                }
+            });
+        });
+}
+
+fn despawn_promotion_picker(
+    mut commands: Commands,
+    q_despawn: Query<Entity, With<OnPromotionScreen>>,
+) {
+    for id in q_despawn.iter() {
+        commands.entity(id).despawn_recursive();
+    }
+}
+
+fn handle_promotion_button_clicked(
+    interaction_query: Query<(&Interaction, &PromotionButton), Changed<Interaction>>,
+    mut q_chess_piece: Query<(&mut BoardPiece, &mut Handle<Image>)>,
+    mut move_state: ResMut<GameMoveState>,
+    mut next_state: ResMut<NextState<PromotionState>>,
+    assets: Res<AssetServer>,
+) {
+    let Some(promotion_cell) = move_state.promotion_cell else {
+        return;
+    };
+    for (interaction, promotion_button) in interaction_query.iter() {
+        if !Interaction::Clicked.eq(interaction) {
+            continue;
+        }
+        for (mut chess_piece, mut image) in q_chess_piece.iter_mut() {
+            if chess_piece.pos == promotion_cell {
+                chess_piece.piece_type = promotion_button.0.clone();
+                *image = AssetsHelper::load_piece_image(
+                    &chess_piece.color,
+                    &chess_piece.piece_type,
+                    &assets,
+                );
+            }
+        }
+
+        let pieces: Vec<&ChessPiece> = q_chess_piece.iter().map(|(cp, _)| &cp.0).collect();
+        move_state.complete_promotion(&promotion_button.0, &pieces);
+        move_state.next_move();
+        next_state.set(PromotionState::Inactive);
+        return;
+    }
+}
@@ -0,0 +1,285 @@
+use bevy::{app::AppExit, prelude::*};
+
+use chess_engine::fen_parser::FenParser;
+
+use crate::{
+    chess_board_plugin::PiecesStore,
+    models::{
+        app_state::AppState,
+        board_piece::BoardPiece,
+        clock::Clock,
+        common_resources::{FontHolder, GameMode, GameState, LoadedGame},
+        game_move_state::GameMoveState,
+        save_game::{SaveGame, SaveGameError, AUTOSAVE_SLOT, SAVE_SLOT_COUNT},
+    },
+    titles::Titles,
+    ui_menu_plugin::{handle_ui_buttons_styles, NORMAL_BUTTON},
+};
+
+#[derive(Component)]
+enum SaveMenuButton {
+    Save(usize),
+    Load(usize),
+    Back,
+}
+
+// name, time and move of the game in the slot
+#[derive(Component)]
+struct SlotText(usize);
+
+#[derive(Component)]
+struct OnSaveScreen;
+
+pub struct SaveGamePlugin;
+
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(autosave.in_schedule(OnExit(AppState::Game)))
+            .add_system(
+                autosave
+                    .in_base_set(CoreSet::Last)
+                    .run_if(in_state(AppState::Game).and_then(on_event::<AppExit>())),
+            )
+            .add_system(setup_save_menu.in_schedule(OnEnter(AppState::SaveMenu)))
+            .add_system(despawn_save_menu.in_schedule(OnExit(AppState::SaveMenu)))
+            .add_systems(
+                (handle_ui_buttons_styles, handle_save_menu_clicked)
+                    .in_set(OnUpdate(AppState::SaveMenu)),
+            );
+    }
+}
+
+// reads a saved game into the resources the game screen starts from on GameState::LOAD
+pub fn load_saved_game(
+    slot: usize,
+    loaded_game: &mut LoadedGame,
+    game_mode: &mut GameMode,
+    game_state: &mut GameState,
+) -> bool {
+    match SaveGame::read(slot) {
+        Ok(save_game) => {
+            loaded_game.fen = Some(save_game.fen());
+            loaded_game.move_state = Some(save_game.move_state);
+            loaded_game.clock = Some(save_game.clock);
+            game_mode.computer_color = save_game.computer_color;
+            game_mode.player_color = save_game.player_color;
+            *game_state = GameState::LOAD;
+            true
+        }
+        Err(err) => {
+            warn!("failed to load saved game {}: {}", slot, err);
+            false
+        }
+    }
+}
+
+// the game left on the game screen or with the window closed is kept for Continue
+fn autosave(
+    q_chess_piece: Query<&BoardPiece>,
+    move_state: Res<GameMoveState>,
+    clock: Res<Clock>,
+    game_mode: Res<GameMode>,
+) {
+    // a network game is not kept, the joined side also leaves the game screen
+    // to set up the host's position before it has one of its own
+    if game_mode.net_role.is_some() {
+        return;
+    }
+    let save_game = SaveGame {
+        name: SaveGame::default_name(&game_mode.computer_color),
+        saved_at: SaveGame::now(),
+        pieces: q_chess_piece.iter().map(|cp| cp.0.clone()).collect(),
+        move_state: move_state.rules.clone(),
+        clock: clock.clone(),
+        computer_color: game_mode.computer_color.clone(),
+        player_color: game_mode.player_color.clone(),
+    };
+    if let Err(err) = save_game.write(AUTOSAVE_SLOT) {
+        warn!("failed to autosave the game: {}", err);
+    }
+}
+
+// the game left on the game screen, the autosaved one before any game of this run
+fn current_game(
+    pieces_store: &PiecesStore,
+    move_state: Option<&GameMoveState>,
+    clock: &Clock,
+    game_mode: &GameMode,
+) -> Result<SaveGame, SaveGameError> {
+    let pieces = pieces_store
+        .state
+        .as_ref()
+        .and_then(|fen| FenParser::parse_fen(fen).ok())
+        .map(|(pieces, _)| pieces);
+    let (Some(pieces), Some(move_state)) = (pieces, move_state) else {
+        let mut save_game = SaveGame::read(AUTOSAVE_SLOT)?;
+        save_game.saved_at = SaveGame::now();
+        return Ok(save_game);
+    };
+    Ok(SaveGame {
+        name: SaveGame::default_name(&game_mode.computer_color),
+        saved_at: SaveGame::now(),
+        pieces,
+        move_state: move_state.rules.clone(),
+        clock: clock.clone(),
+        computer_color: game_mode.computer_color.clone(),
+        player_color: game_mode.player_color.clone(),
+    })
+}
+
+fn slot_label(slot: usize, titles: &Titles) -> String {
+    let name = match slot {
+        AUTOSAVE_SLOT => titles.autosave.clone(),
+        _ => format!("{} {}", titles.save_slot, slot),
+    };
+    match SaveGame::read(slot) {
+        Ok(save_game) => format!(
+            "{}: {} {}, {} {} {}",
+            name,
+            save_game.name,
+            save_game.move_state.result(),
+            save_game.saved_at_label(),
+            titles.save_move,
+            save_game.move_state.fullmove_number()
+        ),
+        Err(_) => format!("{}: {}", name, titles.save_empty),
+    }
+}
+
+fn setup_save_menu(mut commands: Commands, font_holder: Res<FontHolder>, titles: Res<Titles>) {
+    let button = ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(200.0), Val::Px(50.0)),
+            margin: UiRect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    };
+    let text_style = TextStyle {
+        font: font_holder.font.clone(),
+        font_size: 24.,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+    let slot_style = Style {
+        size: Size::new(Val::Px(720.0), Val::Px(50.0)),
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                margin: UiRect::all(Val::Auto),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(OnSaveScreen)
+        .with_children(|node| {
+            for slot in AUTOSAVE_SLOT..=SAVE_SLOT_COUNT {
+                node.spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(NodeBundle {
+                        style: slot_style.clone(),
+                        ..default()
+                    })
+                    .with_children(|slot_node| {
+                        slot_node
+                            .spawn(TextBundle::from_section(
+                                slot_label(slot, &titles),
+                                text_style.clone(),
+                            ))
+                            .insert(SlotText(slot));
+                    });
+                    // the autosave is only written by leaving a game
+                    if slot != AUTOSAVE_SLOT {
+                        row.spawn(button.clone())
+                            .with_children(|button| {
+                                button.spawn(TextBundle::from_section(
+                                    titles.button_save.clone(),
+                                    text_style.clone(),
+                                ));
+                            })
+                            .insert(SaveMenuButton::Save(slot));
+                    }
+                    row.spawn(button.clone())
+                        .with_children(|button| {
+                            button.spawn(TextBundle::from_section(
+                                titles.button_load.clone(),
+                                text_style.clone(),
+                            ));
+                        })
+                        .insert(SaveMenuButton::Load(slot));
+                });
+            }
+        })
+        .with_children(|node| {
+            node.spawn(button.clone())
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section(
+                        titles.button_back.clone(),
+                        text_style.clone(),
+                    ));
+                })
+                .insert(SaveMenuButton::Back);
+        });
+}
+
+fn despawn_save_menu(mut commands: Commands, q_despawn: Query<Entity, With<OnSaveScreen>>) {
+    for entity in q_despawn.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// a slot is saved with the last game left on the game screen
+#[allow(clippy::too_many_arguments)]
+fn handle_save_menu_clicked(
+    q_interaction: Query<(&Interaction, &SaveMenuButton), Changed<Interaction>>,
+    mut q_slot_text: Query<(&SlotText, &mut Text)>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut loaded_game: ResMut<LoadedGame>,
+    mut game_mode: ResMut<GameMode>,
+    mut game_state: ResMut<GameState>,
+    pieces_store: Res<PiecesStore>,
+    move_state: Option<Res<GameMoveState>>,
+    clock: Res<Clock>,
+    titles: Res<Titles>,
+) {
+    for (interaction, button) in q_interaction.iter() {
+        if !Interaction::Clicked.eq(interaction) {
+            continue;
+        }
+        match button {
+            SaveMenuButton::Save(slot) => {
+                let saved = current_game(&pieces_store, move_state.as_deref(), &clock, &game_mode)
+                    .and_then(|save_game| save_game.write(*slot));
+                if let Err(err) = saved {
+                    warn!("failed to save the game to slot {}: {}", slot, err);
+                }
+                for (slot_text, mut text) in q_slot_text.iter_mut() {
+                    if slot_text.0 == *slot {
+                        text.sections[0].value = slot_label(*slot, &titles);
+                    }
+                }
+            }
+            SaveMenuButton::Load(slot) => {
+                if load_saved_game(*slot, &mut loaded_game, &mut game_mode, &mut game_state) {
+                    app_state.set(AppState::Game);
+                }
+            }
+            SaveMenuButton::Back => app_state.set(AppState::MainMenu),
+        }
+    }
+}
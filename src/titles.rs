@@ -1,5 +1,9 @@
 use bevy::prelude::Resource;
 
+use chess_engine::{chess_move_state::DrawReason, common_chess::ChessColor};
+
+use crate::{models::clock::ClockPreset, net_plugin::NetStatus};
+
 pub enum TitleLocale {
     RU,
     EN,
@@ -9,32 +13,198 @@ pub struct Titles {
     pub title: String,
     pub check: String,
     pub mate: String,
+    pub stalemate: String,
+    pub draw_fifty_moves: String,
+    pub draw_threefold_repetition: String,
+    pub draw_insufficient_material: String,
+    pub draw_agreement: String,
+    pub resigned: String,
     pub turn: String,
+    pub promotion: String,
+    pub thinking: String,
+    pub flag_fall: String,
+    pub clock_white: String,
+    pub clock_black: String,
+    pub clock_unlimited: String,
+    pub clock_bullet: String,
+    pub clock_blitz: String,
+    pub clock_rapid: String,
+    pub clock_custom: String,
     pub button_new_game: String,
     pub button_continue_game: String,
+    pub button_play_vs_computer: String,
+    pub button_play_as_white: String,
+    pub button_play_as_black: String,
+    pub button_host_game: String,
+    pub button_join_game: String,
+    pub button_resign: String,
+    pub button_offer_draw: String,
+    pub net_waiting: String,
+    pub net_connecting: String,
+    pub net_playing: String,
+    pub net_disconnected: String,
+    pub net_draw_offered: String,
+    pub button_saves: String,
+    pub button_save: String,
+    pub button_load: String,
+    pub button_back: String,
+    pub save_slot: String,
+    pub autosave: String,
+    pub save_empty: String,
+    pub save_move: String,
     pub button_exit_game: String,
+    pub button_undo: String,
+    pub move_list: String,
+    pub browsing: String,
+    pub button_redo: String,
 }
 
 impl Titles {
+    pub fn draw_reason(&self, reason: &DrawReason) -> String {
+        match reason {
+            DrawReason::FiftyMoves => self.draw_fifty_moves.clone(),
+            DrawReason::ThreefoldRepetition => self.draw_threefold_repetition.clone(),
+            DrawReason::InsufficientMaterial => self.draw_insufficient_material.clone(),
+            DrawReason::Agreement => self.draw_agreement.clone(),
+        }
+    }
+
+    pub fn play_as(&self, color: &ChessColor) -> String {
+        match color {
+            ChessColor::WHITE => self.button_play_as_white.clone(),
+            ChessColor::BLACK => self.button_play_as_black.clone(),
+        }
+    }
+
+    pub fn clock(&self, color: &ChessColor) -> String {
+        match color {
+            ChessColor::WHITE => self.clock_white.clone(),
+            ChessColor::BLACK => self.clock_black.clone(),
+        }
+    }
+
+    pub fn clock_preset(&self, preset: &ClockPreset) -> String {
+        let name = match preset {
+            ClockPreset::UNLIMITED => return self.clock_unlimited.clone(),
+            ClockPreset::BULLET => &self.clock_bullet,
+            ClockPreset::BLITZ => &self.clock_blitz,
+            ClockPreset::RAPID => &self.clock_rapid,
+            ClockPreset::CUSTOM(_) => &self.clock_custom,
+        };
+        match preset.time_control() {
+            Some(time_control) => format!("{} {}", name, time_control),
+            None => name.clone(),
+        }
+    }
+
+    pub fn net_status(&self, status: &NetStatus) -> String {
+        match status {
+            NetStatus::WAITING => self.net_waiting.clone(),
+            NetStatus::CONNECTING => self.net_connecting.clone(),
+            NetStatus::PLAYING => self.net_playing.clone(),
+            NetStatus::DISCONNECTED => self.net_disconnected.clone(),
+        }
+    }
+
     pub fn new(locale: TitleLocale) -> Titles {
         match locale {
             TitleLocale::EN => Titles {
                 title: "Chess Game".to_string(),
                 check: "Check".to_string(),
                 mate: "Mate".to_string(),
+                stalemate: "Stalemate".to_string(),
+                draw_fifty_moves: "Draw: fifty moves".to_string(),
+                draw_threefold_repetition: "Draw: repetition".to_string(),
+                draw_insufficient_material: "Draw: no material".to_string(),
+                draw_agreement: "Draw: agreed".to_string(),
+                resigned: "Resigned".to_string(),
                 turn: "Turn".to_string(),
+                promotion: "Promote to".to_string(),
+                thinking: "Thinking...".to_string(),
+                flag_fall: "Time out".to_string(),
+                clock_white: "White".to_string(),
+                clock_black: "Black".to_string(),
+                clock_unlimited: "No clock".to_string(),
+                clock_bullet: "Bullet".to_string(),
+                clock_blitz: "Blitz".to_string(),
+                clock_rapid: "Rapid".to_string(),
+                clock_custom: "Clock".to_string(),
                 button_new_game: "New Game".to_string(),
                 button_continue_game: "Continue".to_string(),
+                button_play_vs_computer: "Play vs Computer".to_string(),
+                button_play_as_white: "Play as White".to_string(),
+                button_play_as_black: "Play as Black".to_string(),
+                button_host_game: "Host Game".to_string(),
+                button_join_game: "Join Game".to_string(),
+                button_resign: "Resign".to_string(),
+                button_offer_draw: "Offer Draw".to_string(),
+                net_waiting: "Waiting for opponent".to_string(),
+                net_connecting: "Connecting".to_string(),
+                net_playing: "Opponent connected".to_string(),
+                net_disconnected: "Disconnected".to_string(),
+                net_draw_offered: "Draw offered".to_string(),
+                button_saves: "Saved Games".to_string(),
+                button_save: "Save".to_string(),
+                button_load: "Load".to_string(),
+                button_back: "Back".to_string(),
+                save_slot: "Slot".to_string(),
+                autosave: "Autosave".to_string(),
+                save_empty: "empty".to_string(),
+                save_move: "move".to_string(),
                 button_exit_game: "Exit".to_string(),
+                button_undo: "Undo".to_string(),
+                move_list: "Moves".to_string(),
+                browsing: "Review, End resumes".to_string(),
+                button_redo: "Redo".to_string(),
             },
             TitleLocale::RU => Titles {
                 title: "Шахматы".to_string(),
                 check: "Шах".to_string(),
                 mate: "Мат".to_string(),
+                stalemate: "Пат".to_string(),
+                draw_fifty_moves: "Ничья: 50 ходов".to_string(),
+                draw_threefold_repetition: "Ничья: повторение".to_string(),
+                draw_insufficient_material: "Ничья: мало фигур".to_string(),
+                draw_agreement: "Ничья: согласие".to_string(),
+                resigned: "Сдался".to_string(),
                 turn: "Ход".to_string(),
+                promotion: "Превратить в".to_string(),
+                thinking: "Думаю...".to_string(),
+                flag_fall: "Время вышло".to_string(),
+                clock_white: "Белые".to_string(),
+                clock_black: "Чёрные".to_string(),
+                clock_unlimited: "Без часов".to_string(),
+                clock_bullet: "Пуля".to_string(),
+                clock_blitz: "Блиц".to_string(),
+                clock_rapid: "Рапид".to_string(),
+                clock_custom: "Часы".to_string(),
                 button_new_game: "Новая игра".to_string(),
                 button_continue_game: "Продолжить".to_string(),
+                button_play_vs_computer: "Против компьютера".to_string(),
+                button_play_as_white: "Играть белыми".to_string(),
+                button_play_as_black: "Играть чёрными".to_string(),
+                button_host_game: "Создать игру".to_string(),
+                button_join_game: "Подключиться".to_string(),
+                button_resign: "Сдаться".to_string(),
+                button_offer_draw: "Ничья?".to_string(),
+                net_waiting: "Ждём соперника".to_string(),
+                net_connecting: "Подключение".to_string(),
+                net_playing: "Соперник на связи".to_string(),
+                net_disconnected: "Связь потеряна".to_string(),
+                net_draw_offered: "Предложена ничья".to_string(),
+                button_saves: "Сохранения".to_string(),
+                button_save: "Сохранить".to_string(),
+                button_load: "Загрузить".to_string(),
+                button_back: "Назад".to_string(),
+                save_slot: "Слот".to_string(),
+                autosave: "Автосохранение".to_string(),
+                save_empty: "пусто".to_string(),
+                save_move: "ход".to_string(),
                 button_exit_game: "Выход".to_string(),
+                button_undo: "Отменить".to_string(),
+                move_list: "Ходы".to_string(),
+                browsing: "Просмотр, End - к игре".to_string(),
+                button_redo: "Вернуть".to_string(),
             },
         }
     }
@@ -3,14 +3,17 @@ use bevy::{app::AppExit, prelude::*};
 use crate::{
     models::{
         app_state::AppState,
-        common_resources::{FontHolder, GameState},
+        clock::Clock,
+        common_resources::{FontHolder, GameMode, GameState, LoadedGame, NetRole},
+        save_game::AUTOSAVE_SLOT,
     },
+    save_game_plugin::load_saved_game,
     titles::Titles,
 };
 
 pub struct UiMenuPlugin;
 
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+pub const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 
@@ -18,9 +21,21 @@ const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 enum MenuButton {
     NewGame,
     Continue,
+    PlayVsComputer,
+    PlayerColor,
+    ClockPreset,
+    HostGame,
+    JoinGame,
+    Saves,
     Exit,
 }
 
+#[derive(Component)]
+struct PlayerColorText;
+
+#[derive(Component)]
+struct ClockPresetText;
+
 #[derive(Component)]
 struct OnGameScreen;
 
@@ -39,10 +54,16 @@ impl Plugin for UiMenuPlugin {
     }
 }
 
-fn setup_ui_menu(mut commands: Commands, font_holder: Res<FontHolder>, titles: Res<Titles>) {
+fn setup_ui_menu(
+    mut commands: Commands,
+    font_holder: Res<FontHolder>,
+    titles: Res<Titles>,
+    game_mode: Res<GameMode>,
+    clock: Res<Clock>,
+) {
     let button = ButtonBundle {
         style: Style {
-            size: Size::new(Val::Px(200.0), Val::Px(65.0)),
+            size: Size::new(Val::Px(360.0), Val::Px(65.0)),
             margin: UiRect::all(Val::Auto),
             justify_content: JustifyContent::Center,
             align_items: AlignItems::Center,
@@ -88,6 +109,62 @@ fn setup_ui_menu(mut commands: Commands, font_holder: Res<FontHolder>, titles: R
                 })
                 .insert(MenuButton::Continue);
         })
+        .with_children(|node| {
+            node.spawn(button.clone())
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section(
+                        titles.button_play_vs_computer.clone(),
+                        text_style.clone(),
+                    ));
+                })
+                .insert(MenuButton::PlayVsComputer);
+        })
+        .with_children(|node| {
+            node.spawn(button.clone())
+                .with_children(|button| {
+                    button
+                        .spawn(TextBundle::from_section(
+                            titles.play_as(&game_mode.player_color),
+                            text_style.clone(),
+                        ))
+                        .insert(PlayerColorText);
+                })
+                .insert(MenuButton::PlayerColor);
+        })
+        .with_children(|node| {
+            node.spawn(button.clone())
+                .with_children(|button| {
+                    button
+                        .spawn(TextBundle::from_section(
+                            titles.clock_preset(&clock.preset),
+                            text_style.clone(),
+                        ))
+                        .insert(ClockPresetText);
+                })
+                .insert(MenuButton::ClockPreset);
+        })
+        .with_children(|node| {
+            for (menu_button, title) in [
+                (MenuButton::HostGame, &titles.button_host_game),
+                (MenuButton::JoinGame, &titles.button_join_game),
+            ] {
+                node.spawn(button.clone())
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(title.clone(), text_style.clone()));
+                    })
+                    .insert(menu_button);
+            }
+        })
+        .with_children(|node| {
+            node.spawn(button.clone())
+                .with_children(|button| {
+                    button.spawn(TextBundle::from_section(
+                        titles.button_saves.clone(),
+                        text_style.clone(),
+                    ));
+                })
+                .insert(MenuButton::Saves);
+        })
         .with_children(|node| {
             node.spawn(button.clone())
                 .with_children(|button| {
@@ -106,7 +183,7 @@ fn despawn_ui_menu(mut commands: Commands, q_dispawn: Query<Entity, With<OnGameS
     }
 }
 
-fn handle_ui_buttons_styles(
+pub fn handle_ui_buttons_styles(
     mut interaction_query: Query<
         (&Interaction, &mut BackgroundColor),
         (Changed<Interaction>, With<Button>),
@@ -127,21 +204,66 @@ fn handle_ui_buttons_styles(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_button_clicked(
     new_game_interaction_query: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
     mut app_state: ResMut<NextState<AppState>>,
     mut exit: EventWriter<AppExit>,
     mut game_state: ResMut<GameState>,
+    mut game_mode: ResMut<GameMode>,
+    mut loaded_game: ResMut<LoadedGame>,
+    mut clock: ResMut<Clock>,
+    mut q_player_color_text: Query<&mut Text, With<PlayerColorText>>,
+    mut q_clock_preset_text: Query<&mut Text, (With<ClockPresetText>, Without<PlayerColorText>)>,
+    titles: Res<Titles>,
 ) {
     for (interaction, menu_button) in new_game_interaction_query.iter() {
         if Interaction::Clicked.eq(interaction) {
             match menu_button {
                 MenuButton::NewGame => {
                     *game_state = GameState::NEW;
+                    game_mode.computer_color = None;
+                    app_state.set(AppState::Game)
+                }
+                MenuButton::PlayVsComputer => {
+                    *game_state = GameState::NEW;
+                    game_mode.computer_color = Some(game_mode.player_color.opposite());
+                    app_state.set(AppState::Game)
+                }
+                MenuButton::PlayerColor => {
+                    game_mode.player_color = game_mode.player_color.opposite();
+                    for mut text in q_player_color_text.iter_mut() {
+                        text.sections[0].value = titles.play_as(&game_mode.player_color);
+                    }
+                }
+                MenuButton::ClockPreset => {
+                    clock.preset = clock.preset.next();
+                    for mut text in q_clock_preset_text.iter_mut() {
+                        text.sections[0].value = titles.clock_preset(&clock.preset);
+                    }
+                }
+                // the host plays the side picked above, the joining player the other one
+                MenuButton::HostGame | MenuButton::JoinGame => {
+                    *game_state = GameState::NEW;
+                    game_mode.computer_color = None;
+                    game_mode.net_role = match menu_button {
+                        MenuButton::HostGame => Some(NetRole::HOST),
+                        _ => Some(NetRole::JOIN),
+                    };
                     app_state.set(AppState::Game)
                 }
+                MenuButton::Saves => app_state.set(AppState::SaveMenu),
+                // the autosave keeps the last game across restarts,
+                // the one in memory is continued if it could not be written
                 MenuButton::Continue => {
-                    *game_state = GameState::CONTINUE;
+                    if !load_saved_game(
+                        AUTOSAVE_SLOT,
+                        &mut loaded_game,
+                        &mut game_mode,
+                        &mut game_state,
+                    ) {
+                        *game_state = GameState::CONTINUE;
+                    }
                     app_state.set(AppState::Game)
                 }
                 MenuButton::Exit => exit.send(AppExit),
@@ -0,0 +1,283 @@
+use bevy::prelude::*;
+use chess_engine::chess_piece::ChessPiece;
+
+use crate::{
+    assets_helper::AssetsHelper,
+    chess_board_plugin::PIECES_Z,
+    models::{
+        app_state::{AppState, BrowseState, PromotionState},
+        board_piece::BoardPiece,
+        common_resources::{Board, FontHolder, GameMode},
+        game_move_state::GameMoveState,
+        removed_chess_piece::{ChessPieceRemovedEvent, ChessPieceRestoredEvent},
+    },
+    titles::Titles,
+    ui_menu_plugin::handle_ui_buttons_styles,
+};
+
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+
+#[derive(Component, Clone, Copy)]
+enum UndoButton {
+    Undo,
+    Redo,
+}
+
+#[derive(Component)]
+struct OnUndoScreen;
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ChessPieceRestoredEvent>()
+            .add_system(setup_undo_buttons.in_schedule(OnEnter(AppState::Game)))
+            .add_system(despawn_undo_buttons.in_schedule(OnExit(AppState::Game)))
+            .add_system(handle_ui_buttons_styles.in_set(OnUpdate(AppState::Game)))
+            .add_system(
+                handle_undo_system
+                    .run_if(in_state(PromotionState::Inactive))
+                    .run_if(in_state(BrowseState::Latest))
+                    .in_set(OnUpdate(AppState::Game)),
+            );
+    }
+}
+
+// moves of a network game are not taken back, the other player has seen them
+fn setup_undo_buttons(
+    mut commands: Commands,
+    font_holder: Res<FontHolder>,
+    titles: Res<Titles>,
+    game_mode: Res<GameMode>,
+) {
+    if game_mode.net_role.is_some() {
+        return;
+    }
+    let button = ButtonBundle {
+        style: Style {
+            size: Size::new(Val::Px(150.0), Val::Px(50.0)),
+            margin: UiRect::all(Val::Px(5.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: NORMAL_BUTTON.into(),
+        ..default()
+    };
+    let text_style = TextStyle {
+        font: font_holder.font.clone(),
+        font_size: 24.,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(OnUndoScreen)
+        .with_children(|node| {
+            for (undo_button, title) in [
+                (UndoButton::Undo, &titles.button_undo),
+                (UndoButton::Redo, &titles.button_redo),
+            ] {
+                node.spawn(button.clone())
+                    .with_children(|button| {
+                        button.spawn(TextBundle::from_section(title.clone(), text_style.clone()));
+                    })
+                    .insert(undo_button);
+            }
+        });
+}
+
+fn despawn_undo_buttons(mut commands: Commands, q_despawn: Query<Entity, With<OnUndoScreen>>) {
+    for id in q_despawn.iter() {
+        commands.entity(id).despawn_recursive();
+    }
+}
+
+// ctrl+z takes the last move back, ctrl+y or ctrl+shift+z makes it again
+fn pressed_undo_button(keys: &Input<KeyCode>) -> Option<UndoButton> {
+    let is_control = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let is_shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    if !is_control {
+        return None;
+    }
+    if keys.just_pressed(KeyCode::Y) || (is_shift && keys.just_pressed(KeyCode::Z)) {
+        Some(UndoButton::Redo)
+    } else if keys.just_pressed(KeyCode::Z) {
+        Some(UndoButton::Undo)
+    } else {
+        None
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_undo_system(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    interaction_query: Query<(&Interaction, &UndoButton), Changed<Interaction>>,
+    mut move_state: ResMut<GameMoveState>,
+    mut q_chess_piece: Query<(&mut BoardPiece, &mut Transform, &mut Handle<Image>)>,
+    mut piece_removed_event_writer: EventWriter<ChessPieceRemovedEvent>,
+    mut piece_restored_event_writer: EventWriter<ChessPieceRestoredEvent>,
+    board: Res<Board>,
+    assets: Res<AssetServer>,
+    game_mode: Res<GameMode>,
+) {
+    if game_mode.net_role.is_some() {
+        return;
+    }
+    let clicked_button = interaction_query
+        .iter()
+        .find(|(interaction, _)| Interaction::Clicked.eq(interaction))
+        .map(|(_, undo_button)| *undo_button);
+    let Some(undo_button) = clicked_button.or_else(|| pressed_undo_button(&keys)) else {
+        return;
+    };
+
+    match undo_button {
+        UndoButton::Undo => {
+            undo_board_move(
+                &mut commands,
+                &mut move_state,
+                &mut q_chess_piece,
+                &mut piece_restored_event_writer,
+                &board,
+                &assets,
+            );
+        }
+        UndoButton::Redo => {
+            redo_board_move(
+                &mut move_state,
+                &mut q_chess_piece,
+                &mut piece_removed_event_writer,
+                &board,
+                &assets,
+            );
+        }
+    }
+}
+
+// takes the last move back on the board, a captured piece comes back from the tray
+fn undo_board_move(
+    commands: &mut Commands,
+    move_state: &mut GameMoveState,
+    q_chess_piece: &mut Query<(&mut BoardPiece, &mut Transform, &mut Handle<Image>)>,
+    piece_restored_event_writer: &mut EventWriter<ChessPieceRestoredEvent>,
+    board: &Board,
+    assets: &AssetServer,
+) {
+    let mut pieces: Vec<ChessPiece> = q_chess_piece
+        .iter()
+        .map(|(cp, _, _)| cp.0.clone())
+        .collect();
+    let Some(chess_move) = move_state.undo_move(&mut pieces) else {
+        return;
+    };
+    debug!(
+        "undo {}{}",
+        chess_move.san,
+        en_passant_suffix(chess_move.is_en_passant)
+    );
+    for (mut chess_piece, mut transform, mut image) in q_chess_piece.iter_mut() {
+        if chess_piece.pos == chess_move.to {
+            chess_piece.0 = chess_move.piece.clone();
+        } else if let Some((rook_from, _)) = chess_move
+            .castling_rook
+            .filter(|(_, rook_to)| chess_piece.pos == *rook_to)
+        {
+            chess_piece.pos = rook_from;
+        } else {
+            continue;
+        }
+        place_piece(&chess_piece, &mut transform, &mut image, board, assets);
+    }
+    if let Some(captured_piece) = chess_move.captured_piece {
+        let (x, y) = board.coordinates(&captured_piece.pos);
+        AssetsHelper::spawn_piece(
+            commands,
+            captured_piece.clone(),
+            Vec3::new(x, y, PIECES_Z),
+            assets,
+            board,
+        );
+        piece_restored_event_writer.send(ChessPieceRestoredEvent {
+            chess_piece: captured_piece,
+        });
+    }
+}
+
+// makes the last taken back move again on the board
+fn redo_board_move(
+    move_state: &mut GameMoveState,
+    q_chess_piece: &mut Query<(&mut BoardPiece, &mut Transform, &mut Handle<Image>)>,
+    piece_removed_event_writer: &mut EventWriter<ChessPieceRemovedEvent>,
+    board: &Board,
+    assets: &AssetServer,
+) {
+    let mut pieces: Vec<ChessPiece> = q_chess_piece
+        .iter()
+        .map(|(cp, _, _)| cp.0.clone())
+        .collect();
+    let Some(chess_move) = move_state.redo_move(&mut pieces) else {
+        return;
+    };
+    debug!(
+        "redo {}{}",
+        chess_move.san,
+        en_passant_suffix(chess_move.is_en_passant)
+    );
+    for (mut chess_piece, mut transform, mut image) in q_chess_piece.iter_mut() {
+        if chess_piece.pos == chess_move.piece.pos {
+            chess_piece.pos = chess_move.to;
+            if let Some(piece_type) = &chess_move.promotion {
+                chess_piece.piece_type = piece_type.clone();
+            }
+        } else if let Some((_, rook_to)) = chess_move
+            .castling_rook
+            .filter(|(rook_from, _)| chess_piece.pos == *rook_from)
+        {
+            chess_piece.pos = rook_to;
+        } else {
+            continue;
+        }
+        place_piece(&chess_piece, &mut transform, &mut image, board, assets);
+    }
+    if let Some(captured_piece) = chess_move.captured_piece {
+        piece_removed_event_writer.send(ChessPieceRemovedEvent {
+            chess_piece: captured_piece,
+        });
+    }
+}
+
+fn en_passant_suffix(is_en_passant: bool) -> &'static str {
+    if is_en_passant {
+        " e.p."
+    } else {
+        ""
+    }
+}
+
+// puts the sprite on the cell of the piece, the image follows a promotion or its undo
+fn place_piece(
+    chess_piece: &ChessPiece,
+    transform: &mut Transform,
+    image: &mut Handle<Image>,
+    board: &Board,
+    assets: &AssetServer,
+) {
+    let (x, y) = board.coordinates(&chess_piece.pos);
+    transform.translation.x = x;
+    transform.translation.y = y;
+    *image = AssetsHelper::load_piece_image(&chess_piece.color, &chess_piece.piece_type, assets);
+}